@@ -0,0 +1,37 @@
+// Darkness overlay for `src/lighting.rs`. Everything is dark apart from pools of
+// light around each light source; lit areas get a faint wash of the light's
+// colour. Kept to plain uniforms so it runs on software renderers too.
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// Array sizes have to match MAX_LIGHTS in lighting.rs
+struct Lighting {
+    darkness: vec4<f32>,           // rgb: colour of the dark, a: how dark
+    lights: array<vec4<f32>, 32>,  // xy: world position, z: radius, w: intensity
+    colors: array<vec4<f32>, 32>,
+    count: u32,
+};
+
+@group(2) @binding(0) var<uniform> lighting: Lighting;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let position = mesh.world_position.xy;
+
+    var light = 0.0;
+    var tint = vec3<f32>(0.0);
+    for (var i = 0u; i < lighting.count; i++) {
+        let source = lighting.lights[i];
+        let falloff = clamp(1.0 - distance(position, source.xy) / source.z, 0.0, 1.0);
+        let amount = falloff * falloff * source.w;
+        light += amount;
+        tint += lighting.colors[i].rgb * amount;
+    }
+
+    tint = tint / max(light, 0.0001);
+    light = min(light, 1.0);
+
+    let shadow = lighting.darkness.a * (1.0 - light);
+    let color = mix(lighting.darkness.rgb, tint, light);
+    return vec4<f32>(color, max(shadow, 0.15 * light));
+}
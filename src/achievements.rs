@@ -0,0 +1,324 @@
+// Achievements, tracked off gameplay events and kept in profile.json so they
+// carry over between sessions. A toast pops up in the corner on unlock.
+//
+// A "night" runs until the balloon is popped, the next one starts when the shop
+// closes.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+
+use crate::{
+    events::{BalloonPopped, CandyCollected, NightStarted, SackFull},
+    costumes::Costume,
+    shop::Upgrades,
+    tuning::GameTuning,
+    Ghost, GhostState, House, ProgressChanged,
+};
+
+const PROFILE_PATH: &str = "profile.json";
+
+const SACKS_TO_FILL: u32 = 10;
+const QUICK_POP_SECONDS: f32 = 5.0;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load())
+            .init_resource::<Night>()
+            .add_event::<AchievementUnlocked>()
+            .add_systems(
+                Update,
+                (
+                    start_night,
+                    count_full_sacks,
+                    track_house_visits,
+                    track_faded_catches,
+                    track_balloon_unlock,
+                    finish_night,
+                    show_achievement_toasts,
+                    fade_achievement_toasts,
+                    save_profile,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    SackHabit,   // Fill the sack 10 times
+    QuickDraw,   // Pop the balloon within 5 seconds of unlocking it
+    GrandTour,   // Get candy from every lit house in one night
+    Unseen,      // Finish a night without getting caught while Faded
+}
+
+impl Achievement {
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::SackHabit => "Sack Habit",
+            Achievement::QuickDraw => "Quick Draw",
+            Achievement::GrandTour => "Grand Tour",
+            Achievement::Unseen => "Unseen",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Achievement::SackHabit => "Fill the sack 10 times",
+            Achievement::QuickDraw => "Pop the balloon within 5 seconds of unlocking it",
+            Achievement::GrandTour => "Get candy from every lit house in one night",
+            Achievement::Unseen => "Finish a night without getting caught while Faded",
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct AchievementUnlocked(pub Achievement);
+
+// Everything about the player that outlives a session
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub unlocked: Vec<Achievement>,
+    pub sacks_filled: u32,
+    pub costume: Costume,
+}
+
+impl Profile {
+    // Falls back to a fresh profile if the file is missing or unreadable
+    fn load() -> Self {
+        fs::read_to_string(PROFILE_PATH)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn unlock(&mut self, achievement: Achievement, unlocked_events: &mut EventWriter<AchievementUnlocked>) {
+        if !self.unlocked.contains(&achievement) {
+            self.unlocked.push(achievement);
+            unlocked_events.send(AchievementUnlocked(achievement));
+        }
+    }
+}
+
+// Progress towards the per-night achievements
+#[derive(Resource, Default)]
+struct Night {
+    houses_visited: HashSet<Entity>,
+    balloon_unlocked_at: Option<f32>,
+    caught_while_faded: bool,
+    over: bool,
+}
+
+#[derive(Component)]
+struct AchievementToast {
+    timer: Timer,
+}
+
+fn start_night(
+    mut night: ResMut<Night>,
+    mut night_events: EventReader<NightStarted>,
+) {
+    if night_events.read().count() > 0 {
+        *night = Night::default();
+    }
+}
+
+fn count_full_sacks(
+    mut profile: ResMut<Profile>,
+    mut sack_full_events: EventReader<SackFull>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    for _ in sack_full_events.read() {
+        profile.sacks_filled += 1;
+        if profile.sacks_filled >= SACKS_TO_FILL {
+            profile.unlock(Achievement::SackHabit, &mut unlocked_events);
+        }
+    }
+}
+
+fn track_house_visits(
+    mut profile: ResMut<Profile>,
+    mut night: ResMut<Night>,
+    mut collected_events: EventReader<CandyCollected>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+    house_query: Query<(Entity, &House)>,
+) {
+    for event in collected_events.read() {
+        let Some(house) = event.house else {
+            continue;
+        };
+        if night.over || !house_query.get(house).is_ok_and(|(_, house)| house.light_status) {
+            continue;
+        }
+
+        // Houses that are dark right now don't count against the tour
+        night.houses_visited.insert(house);
+        let toured = house_query
+            .iter()
+            .filter(|(_, house)| house.light_status)
+            .all(|(entity, _)| night.houses_visited.contains(&entity));
+        if toured {
+            profile.unlock(Achievement::GrandTour, &mut unlocked_events);
+        }
+    }
+}
+
+// A Faded ghost is caught whenever it lurks within reach of a lit house
+fn track_faded_catches(
+    mut night: ResMut<Night>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
+    house_query: Query<(&Transform, &House)>,
+    ghost_query: Query<(&Transform, &Ghost)>,
+) {
+    if night.over || night.caught_while_faded {
+        return;
+    }
+
+    let caught_range = upgrades.interaction_range(&tuning);
+    let caught = ghost_query
+        .iter()
+        .filter(|(_, ghost)| matches!(ghost.state, GhostState::Faded))
+        .any(|(ghost_transform, _)| {
+            house_query.iter().any(|(house_transform, house)| {
+                house.light_status
+                    && ghost_transform.translation.distance(house_transform.translation) < caught_range
+            })
+        });
+    if caught {
+        night.caught_while_faded = true;
+    }
+}
+
+fn track_balloon_unlock(
+    time: Res<Time>,
+    mut night: ResMut<Night>,
+    mut progress_events: EventReader<ProgressChanged>,
+) {
+    for event in progress_events.read() {
+        if event.percent >= 100.0 && night.balloon_unlocked_at.is_none() {
+            night.balloon_unlocked_at = Some(time.elapsed_seconds());
+        }
+    }
+}
+
+fn finish_night(
+    time: Res<Time>,
+    mut profile: ResMut<Profile>,
+    mut night: ResMut<Night>,
+    mut popped_events: EventReader<BalloonPopped>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    for _ in popped_events.read() {
+        if night.over {
+            continue;
+        }
+        night.over = true;
+
+        let quick = night
+            .balloon_unlocked_at
+            .is_some_and(|unlocked_at| time.elapsed_seconds() - unlocked_at <= QUICK_POP_SECONDS);
+        if quick {
+            profile.unlock(Achievement::QuickDraw, &mut unlocked_events);
+        }
+        if !night.caught_while_faded {
+            profile.unlock(Achievement::Unseen, &mut unlocked_events);
+        }
+    }
+}
+
+fn show_achievement_toasts(
+    mut commands: Commands,
+    mut unlocked_events: EventReader<AchievementUnlocked>,
+    toast_query: Query<(), With<AchievementToast>>,
+) {
+    // Stack new toasts above the ones still showing
+    let showing = toast_query.iter().count();
+
+    for (index, AchievementUnlocked(achievement)) in unlocked_events.read().enumerate() {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(20.0),
+                        bottom: Val::Px(20.0 + 70.0 * (showing + index) as f32),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.1, 0.05, 0.15, 0.9).into(),
+                    border_radius: BorderRadius::all(Val::Px(6.0)),
+                    z_index: ZIndex::Global(50),
+                    ..default()
+                },
+                AchievementToast {
+                    timer: Timer::from_seconds(4.0, TimerMode::Once),
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    format!("Achievement unlocked: {}", achievement.title()),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::srgb(1.0, 0.6, 0.0),
+                        ..default()
+                    },
+                ));
+                parent.spawn(TextBundle::from_section(
+                    achievement.description(),
+                    TextStyle {
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
+
+fn fade_achievement_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toast_query: Query<(Entity, &mut AchievementToast, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, mut toast, mut background_color, children) in toast_query.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Fade out over the last second
+        let alpha = toast.timer.remaining_secs().min(1.0);
+        background_color.0 = background_color.0.with_alpha(0.9 * alpha);
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                for section in text.sections.iter_mut() {
+                    section.style.color = section.style.color.with_alpha(alpha);
+                }
+            }
+        }
+    }
+}
+
+fn save_profile(profile: Res<Profile>) {
+    // Skip the frame it was loaded, nothing has changed yet
+    if !profile.is_changed() || profile.is_added() {
+        return;
+    }
+
+    match serde_json::to_string_pretty(&*profile) {
+        Ok(data) => {
+            if let Err(error) = fs::write(PROFILE_PATH, data) {
+                error!("Failed to save profile: {}", error);
+            }
+        }
+        Err(error) => error!("Failed to save profile: {}", error),
+    }
+}
@@ -0,0 +1,235 @@
+// Sprite-sheet animation. Animated sprites use one of the sheets in
+// assets/sprites/sheets and carry an `Animator` playing a named clip from it;
+// the matching `TextureAtlas` is attached automatically. Ghost clips are picked
+// from their state here; houses pick theirs in `update_house_display`.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::{GameState, Ghost, GhostState, Player};
+
+const FRAME_SIZE: u32 = 534;
+
+// Slower than this counts as floating in place
+const MOVING_SPEED: f32 = 40.0;
+
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SheetLayouts>()
+            .add_systems(
+                Update,
+                (animate_ghosts, play_animations)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // Late enough that anything spawned this frame is drawn from its
+            // first frame rather than the whole sheet
+            .add_systems(PostUpdate, attach_texture_atlases);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sheet {
+    Ghost,  // Every costume's sheet shares this layout
+    House,
+    Balloon,
+}
+
+impl Sheet {
+    const ALL: [Sheet; 3] = [Sheet::Ghost, Sheet::House, Sheet::Balloon];
+
+    fn grid(self) -> (u32, u32) {
+        match self {
+            Sheet::Ghost => (4, 4),
+            Sheet::House => (4, 1),
+            Sheet::Balloon => (4, 2),
+        }
+    }
+
+    pub fn texture(self) -> &'static str {
+        match self {
+            Sheet::Ghost => "sprites/sheets/ghost.png",
+            Sheet::House => "sprites/sheets/house.png",
+            Sheet::Balloon => "sprites/sheets/balloon.png",
+        }
+    }
+
+    fn clip(self, clip: Clip) -> ClipFrames {
+        match (self, clip) {
+            (Sheet::Ghost, Clip::Idle) => ClipFrames::looping(&[0, 1, 2, 3], 6.0),
+            (Sheet::Ghost, Clip::Move) => ClipFrames::looping(&[4, 5, 6, 7], 10.0),
+            (Sheet::Ghost, Clip::FadeOut) => ClipFrames::once(&[8, 9, 10, 11], 12.0),
+            (Sheet::Ghost, Clip::Faded) => ClipFrames::looping(&[12, 13, 14, 15], 6.0),
+            (Sheet::Ghost, Clip::FadeIn) => ClipFrames::once(&[11, 10, 9, 8], 12.0),
+            // House frames run from dark to lit, the middle two are cross-fades.
+            // Lights buzz a few times before settling, like a dodgy bulb
+            (Sheet::House, Clip::LightsOn) => ClipFrames::once(&[0, 2, 0, 1, 2, 3], 12.0),
+            (Sheet::House, Clip::LightsOff) => ClipFrames::once(&[3, 2, 1, 0], 10.0),
+            (Sheet::House, Clip::Dimming) => ClipFrames::looping(&[3, 3, 1, 3, 2, 3, 3, 0, 3, 3, 2], 12.0),
+            (Sheet::Balloon, Clip::Idle) => ClipFrames::looping(&[0, 1, 2, 3, 2, 1], 8.0),
+            (Sheet::Balloon, Clip::Pop) => ClipFrames::once(&[4, 5, 6, 7], 16.0),
+            _ => ClipFrames::once(&[0], 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Clip {
+    Idle,
+    Move,
+    FadeOut,  // Normal to Faded
+    Faded,
+    FadeIn,   // Faded back to Normal
+    Pop,
+    LightsOn,
+    LightsOff,
+    Dimming,  // About to go dark, see `House::lights_out`
+}
+
+struct ClipFrames {
+    frames: &'static [usize],
+    fps: f32,
+    looping: bool,
+}
+
+impl ClipFrames {
+    fn looping(frames: &'static [usize], fps: f32) -> Self {
+        Self { frames, fps, looping: true }
+    }
+
+    fn once(frames: &'static [usize], fps: f32) -> Self {
+        Self { frames, fps, looping: false }
+    }
+}
+
+#[derive(Component)]
+pub struct Animator {
+    sheet: Sheet,
+    clip: Clip,
+    frame: usize,  // Position within the clip, not the sheet
+    timer: Timer,
+    finished: bool,
+    despawn_when_finished: bool,
+}
+
+impl Animator {
+    pub fn new(sheet: Sheet, clip: Clip) -> Self {
+        Self {
+            sheet,
+            clip,
+            frame: 0,
+            timer: Self::frame_timer(sheet, clip),
+            finished: false,
+            despawn_when_finished: false,
+        }
+    }
+
+    // For one-off effects like the balloon popping
+    pub fn despawn_when_finished(mut self) -> Self {
+        self.despawn_when_finished = true;
+        self
+    }
+
+    // Start on the last frame, e.g. a house that's already lit when it spawns
+    pub fn skip_to_end(mut self) -> Self {
+        self.frame = self.sheet.clip(self.clip).frames.len() - 1;
+        self.finished = true;
+        self
+    }
+
+    // Does nothing if the clip is already playing, so it's fine to call every frame
+    pub fn play(&mut self, clip: Clip) {
+        if self.clip != clip {
+            *self = Self {
+                despawn_when_finished: self.despawn_when_finished,
+                ..Self::new(self.sheet, clip)
+            };
+        }
+    }
+
+    fn frame_timer(sheet: Sheet, clip: Clip) -> Timer {
+        Timer::from_seconds(1.0 / sheet.clip(clip).fps, TimerMode::Repeating)
+    }
+
+    fn sheet_index(&self) -> usize {
+        self.sheet.clip(self.clip).frames[self.frame]
+    }
+}
+
+#[derive(Resource)]
+struct SheetLayouts(HashMap<Sheet, Handle<TextureAtlasLayout>>);
+
+impl FromWorld for SheetLayouts {
+    fn from_world(world: &mut World) -> Self {
+        let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+        let handles = Sheet::ALL
+            .into_iter()
+            .map(|sheet| {
+                let (columns, rows) = sheet.grid();
+                let layout = TextureAtlasLayout::from_grid(UVec2::splat(FRAME_SIZE), columns, rows, None, None);
+                (sheet, layouts.add(layout))
+            })
+            .collect();
+        Self(handles)
+    }
+}
+
+fn attach_texture_atlases(
+    mut commands: Commands,
+    layouts: Res<SheetLayouts>,
+    animator_query: Query<(Entity, &Animator), Without<TextureAtlas>>,
+) {
+    for (entity, animator) in animator_query.iter() {
+        commands.entity(entity).insert(TextureAtlas {
+            layout: layouts.0[&animator.sheet].clone(),
+            index: animator.sheet_index(),
+        });
+    }
+}
+
+fn animate_ghosts(mut ghost_query: Query<(&Ghost, &Player, &mut Animator)>) {
+    for (ghost, player, mut animator) in ghost_query.iter_mut() {
+        let clip = match (&ghost.state, animator.clip) {
+            (GhostState::Faded, Clip::FadeOut) if animator.finished => Clip::Faded,
+            (GhostState::Faded, Clip::FadeOut | Clip::Faded) => animator.clip,
+            (GhostState::Faded, _) => Clip::FadeOut,
+            (GhostState::Normal, Clip::FadeIn) if !animator.finished => Clip::FadeIn,
+            (GhostState::Normal, Clip::FadeOut | Clip::Faded) => Clip::FadeIn,
+            (GhostState::Normal, _) if player.velocity.length() > MOVING_SPEED => Clip::Move,
+            (GhostState::Normal, _) => Clip::Idle,
+        };
+        animator.play(clip);
+    }
+}
+
+fn play_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animator_query: Query<(Entity, &mut Animator, &mut TextureAtlas)>,
+) {
+    for (entity, mut animator, mut atlas) in animator_query.iter_mut() {
+        if animator.finished {
+            if animator.despawn_when_finished {
+                commands.entity(entity).despawn_recursive();
+            }
+            continue;
+        }
+
+        let clip = animator.sheet.clip(animator.clip);
+        let steps = animator.timer.tick(time.delta()).times_finished_this_tick() as usize;
+        if clip.looping {
+            animator.frame = (animator.frame + steps) % clip.frames.len();
+        } else {
+            // Finished once the last frame has had its turn on screen
+            animator.finished = animator.frame + steps >= clip.frames.len();
+            animator.frame = (animator.frame + steps).min(clip.frames.len() - 1);
+        }
+
+        let index = animator.sheet_index();
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+}
@@ -0,0 +1,215 @@
+// Background music and positional sound effects.
+//
+// Gameplay systems play one-shot effects through the `Sfx` system param. The
+// listener sits on the camera, so a sound pans with where it happens on screen.
+
+use bevy::{
+    audio::{DefaultSpatialScale, SpatialScale, Volume},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use std::collections::HashMap;
+
+use crate::{
+    events::{BalloonPopped, CandyCollected, CandyDeposited, HousePranked, ShotFired},
+    settings::Settings,
+    House,
+};
+
+// Knocks played over one full `interaction_timer` run
+const KNOCKS_PER_VISIT: u32 = 3;
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // 100 pixels to a metre, otherwise everything off-centre is near silent
+            .insert_resource(DefaultSpatialScale(SpatialScale::new_2d(0.01)))
+            .init_resource::<SoundEffects>()
+            .add_systems(Startup, start_music)
+            .add_systems(
+                Update,
+                (
+                    add_listener_to_camera,
+                    adjust_master_volume,
+                    apply_music_volume,
+                    house_knock_sounds,
+                    play_gameplay_sounds,
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SoundEffect {
+    Knock,
+    CandyPickup,
+    Deposit,
+    Shoot,
+    BalloonPop,
+}
+
+#[derive(Resource)]
+struct SoundEffects {
+    ambience: Handle<AudioSource>,
+    knock: Handle<AudioSource>,
+    candy_pickup: Handle<AudioSource>,
+    deposit: Handle<AudioSource>,
+    shoot: Handle<AudioSource>,
+    balloon_pop: Handle<AudioSource>,
+}
+
+impl FromWorld for SoundEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            ambience: asset_server.load("audio/ambience.wav"),
+            knock: asset_server.load("audio/knock.wav"),
+            candy_pickup: asset_server.load("audio/candy_pickup.wav"),
+            deposit: asset_server.load("audio/deposit.wav"),
+            shoot: asset_server.load("audio/shoot.wav"),
+            balloon_pop: asset_server.load("audio/balloon_pop.wav"),
+        }
+    }
+}
+
+#[derive(Component)]
+struct BackgroundMusic;
+
+// Plays sound effects at the configured volume
+#[derive(SystemParam)]
+pub struct Sfx<'w> {
+    sounds: Res<'w, SoundEffects>,
+    settings: Res<'w, Settings>,
+}
+
+impl Sfx<'_> {
+    pub fn play_at(&self, commands: &mut Commands, effect: SoundEffect, position: Vec3) {
+        self.play_at_speed(commands, effect, position, 1.0);
+    }
+
+    pub fn play_at_speed(&self, commands: &mut Commands, effect: SoundEffect, position: Vec3, speed: f32) {
+        let source = match effect {
+            SoundEffect::Knock => &self.sounds.knock,
+            SoundEffect::CandyPickup => &self.sounds.candy_pickup,
+            SoundEffect::Deposit => &self.sounds.deposit,
+            SoundEffect::Shoot => &self.sounds.shoot,
+            SoundEffect::BalloonPop => &self.sounds.balloon_pop,
+        };
+
+        commands.spawn((
+            AudioBundle {
+                source: source.clone(),
+                settings: PlaybackSettings::DESPAWN
+                    .with_spatial(true)
+                    .with_speed(speed)
+                    .with_volume(Volume::new(self.settings.master_volume * self.settings.effects_volume)),
+            },
+            SpatialBundle::from_transform(Transform::from_translation(position)),
+        ));
+    }
+}
+
+fn start_music(
+    mut commands: Commands,
+    sounds: Res<SoundEffects>,
+    settings: Res<Settings>,
+) {
+    commands.spawn((
+        AudioBundle {
+            source: sounds.ambience.clone(),
+            settings: PlaybackSettings::LOOP
+                .with_volume(Volume::new(settings.master_volume * settings.music_volume)),
+        },
+        BackgroundMusic,
+    ));
+}
+
+fn add_listener_to_camera(
+    mut commands: Commands,
+    camera_query: Query<Entity, Added<Camera2d>>,
+) {
+    for camera in camera_query.iter() {
+        // Ears roughly a house apart
+        commands.entity(camera).insert(SpatialListener::new(4.0));
+    }
+}
+
+// Minus / Equals nudge the master volume without opening the settings menu
+fn adjust_master_volume(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    let step = 0.1;
+    if keyboard.just_pressed(KeyCode::Minus) {
+        settings.master_volume = (settings.master_volume - step).max(0.0);
+    }
+    if keyboard.just_pressed(KeyCode::Equal) {
+        settings.master_volume = (settings.master_volume + step).min(1.0);
+    }
+}
+
+fn apply_music_volume(
+    settings: Res<Settings>,
+    music_query: Query<Ref<AudioSink>, With<BackgroundMusic>>,
+) {
+    for sink in music_query.iter() {
+        // The sink only appears once the music has loaded, so catch that too
+        if settings.is_changed() || sink.is_added() {
+            sink.set_volume(settings.master_volume * settings.music_volume);
+        }
+    }
+}
+
+fn play_gameplay_sounds(
+    mut commands: Commands,
+    sfx: Sfx,
+    mut collected_events: EventReader<CandyCollected>,
+    mut deposited_events: EventReader<CandyDeposited>,
+    mut popped_events: EventReader<BalloonPopped>,
+    mut shot_events: EventReader<ShotFired>,
+    mut prank_events: EventReader<HousePranked>,
+) {
+    for event in collected_events.read() {
+        sfx.play_at(&mut commands, SoundEffect::CandyPickup, event.position);
+    }
+    for event in deposited_events.read() {
+        sfx.play_at(&mut commands, SoundEffect::Deposit, event.position);
+    }
+    for event in popped_events.read() {
+        sfx.play_at(&mut commands, SoundEffect::BalloonPop, event.position);
+    }
+    for event in shot_events.read() {
+        sfx.play_at(&mut commands, SoundEffect::Shoot, event.position);
+    }
+    // A slow, heavy bang on the door
+    for event in prank_events.read() {
+        sfx.play_at_speed(&mut commands, SoundEffect::Knock, event.position, 0.6);
+    }
+}
+
+// Knock on the door while trick-or-treating, a little more urgently each time.
+// In Versus the furthest rival sets the pace
+fn house_knock_sounds(
+    mut commands: Commands,
+    sfx: Sfx,
+    house_query: Query<(Entity, &Transform, &House)>,
+    mut knocks_played: Local<HashMap<Entity, u32>>,
+) {
+    for (entity, transform, house) in house_query.iter() {
+        let progress = house.visit_fraction();
+        if progress <= 0.0 {
+            knocks_played.remove(&entity);
+            continue;
+        }
+
+        let played = knocks_played.entry(entity).or_insert(0);
+        let due = (progress * KNOCKS_PER_VISIT as f32).ceil() as u32;
+        if due > *played {
+            let speed = 1.0 + 0.15 * *played as f32;
+            sfx.play_at_speed(&mut commands, SoundEffect::Knock, transform.translation, speed);
+            *played = due;
+        }
+    }
+}
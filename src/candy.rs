@@ -0,0 +1,372 @@
+// Candy kinds. Houses hand candy out from a loot table for the kind of
+// resident living there, see the level file. Each kind is worth some points,
+// which is what the shop and the balloon bar count, and takes up some slots in
+// the sack. The HUD in the bottom right shows what every ghost is carrying and
+// what has been collected so far, plus any spook points from pranks.
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{Player, PlayerInventory};
+
+const SPOOK_COLOR: Color = Color::srgb(0.55, 0.85, 0.95);
+
+type ChangedCandyQuery<'w, 's> = Query<'w, 's, (), Or<(Changed<CandySack>, Changed<PlayerInventory>)>>;
+
+pub struct CandyPlugin;
+
+impl Plugin for CandyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_candy_hud)
+            .add_systems(Update, update_candy_hud);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub enum CandyKind {
+    Chocolate,
+    Lollipop,
+    FullSizeBar,
+    RottenApple,
+    CursedCandy,
+}
+
+const CANDY_KINDS: [CandyKind; 5] = [
+    CandyKind::Chocolate,
+    CandyKind::Lollipop,
+    CandyKind::FullSizeBar,
+    CandyKind::RottenApple,
+    CandyKind::CursedCandy,
+];
+
+impl CandyKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CandyKind::Chocolate => "Chocolate",
+            CandyKind::Lollipop => "Lollipop",
+            CandyKind::FullSizeBar => "Full-size bar",
+            CandyKind::RottenApple => "Rotten apple",
+            CandyKind::CursedCandy => "Cursed candy",
+        }
+    }
+
+    // Cursed candy takes points away
+    pub fn points(self) -> i32 {
+        match self {
+            CandyKind::Chocolate => 2,
+            CandyKind::Lollipop => 1,
+            CandyKind::FullSizeBar => 4,
+            CandyKind::RottenApple => 0,
+            CandyKind::CursedCandy => -2,
+        }
+    }
+
+    pub fn slots(self) -> u32 {
+        match self {
+            CandyKind::FullSizeBar => 3,
+            CandyKind::RottenApple => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            CandyKind::Chocolate => Color::srgb(0.75, 0.5, 0.3),
+            CandyKind::Lollipop => Color::srgb(1.0, 0.5, 0.8),
+            CandyKind::FullSizeBar => Color::srgb(1.0, 0.85, 0.3),
+            CandyKind::RottenApple => Color::srgb(0.6, 0.7, 0.3),
+            CandyKind::CursedCandy => Color::srgb(0.65, 0.35, 1.0),
+        }
+    }
+}
+
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct CandySack {
+    pub capacity: u32,  // Slots, see `CandyKind::slots`
+    contents: Vec<(CandyKind, i32)>,  // Oldest first, with what banking it really added, see `PlayerInventory::bank`
+}
+
+impl CandySack {
+    pub fn new(capacity: u32) -> Self {
+        Self {
+            capacity,
+            contents: Vec::new(),
+        }
+    }
+
+    pub fn used(&self) -> u32 {
+        self.contents.iter().map(|(kind, _)| kind.slots()).sum()
+    }
+
+    pub fn free(&self) -> u32 {
+        self.capacity.saturating_sub(self.used())
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.free() == 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    // How heavy the sack is, 0.0 empty to 1.0 full
+    pub fn load(&self) -> f32 {
+        (self.used() as f32 / self.capacity.max(1) as f32).min(1.0)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.contents.len() as u32
+    }
+
+    pub fn points(&self) -> i32 {
+        self.contents.iter().map(|(kind, _)| kind.points()).sum()
+    }
+
+    fn count(&self, kind: CandyKind) -> u32 {
+        self.contents.iter().filter(|(candy, _)| *candy == kind).count() as u32
+    }
+
+    pub fn fits(&self, kind: CandyKind) -> bool {
+        kind.slots() <= self.free()
+    }
+
+    // False if it doesn't fit. `banked` is what banking it changed the tally by,
+    // so spilling it can take back exactly that
+    pub fn add(&mut self, kind: CandyKind, banked: i32) -> bool {
+        let fits = self.fits(kind);
+        if fits {
+            self.contents.push((kind, banked));
+        }
+        fits
+    }
+
+    // The most recent candy spills out first, with what each one banked
+    pub fn spill(&mut self, count: u32) -> Vec<(CandyKind, i32)> {
+        let keep = self.contents.len().saturating_sub(count as usize);
+        self.contents.split_off(keep)
+    }
+
+    pub fn empty(&mut self) {
+        self.contents.clear();
+    }
+}
+
+// What a house hands out, as (kind, relative chance) pairs
+#[derive(Component, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct LootTable {
+    entries: Vec<(CandyKind, u32)>,
+}
+
+impl Default for LootTable {
+    fn default() -> Self {
+        Self {
+            entries: vec![(CandyKind::Lollipop, 1)],
+        }
+    }
+}
+
+impl LootTable {
+    // A random candy that fits in `free_slots`, None if nothing does
+    pub fn draw(&self, free_slots: u32) -> Option<CandyKind> {
+        let fitting: Vec<(CandyKind, u32)> = self
+            .entries
+            .iter()
+            .copied()
+            .filter(|(kind, chance)| kind.slots() <= free_slots && *chance > 0)
+            .collect();
+        let total: u32 = fitting.iter().map(|(_, chance)| chance).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut roll = rand::thread_rng().gen_range(0..total);
+        for (kind, chance) in fitting {
+            if roll < chance {
+                return Some(kind);
+            }
+            roll -= chance;
+        }
+        None
+    }
+}
+
+#[derive(Component)]
+struct CandyHud;
+
+fn spawn_candy_hud(mut commands: Commands) {
+    commands.spawn((
+        TextBundle::from_sections([]).with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        }),
+        CandyHud,
+    ));
+}
+
+fn update_candy_hud(
+    inventory: Res<PlayerInventory>,
+    ghost_query: Query<(&Player, &CandySack, Option<&PlayerInventory>)>,
+    changed_query: ChangedCandyQuery,
+    mut hud_query: Query<&mut Text, With<CandyHud>>,
+) {
+    if !inventory.is_changed() && changed_query.is_empty() {
+        return;
+    }
+    let Ok(mut text) = hud_query.get_single_mut() else {
+        return;
+    };
+
+    let mut ghosts: Vec<_> = ghost_query.iter().collect();
+    ghosts.sort_by_key(|(player, ..)| player.id);
+    let label = |player: &Player| if ghosts.len() > 1 { format!("P{} ", player.id + 1) } else { String::new() };
+
+    let mut sections = Vec::new();
+    for (player, candy_sack, _) in &ghosts {
+        let counts = CANDY_KINDS.map(|kind| (kind, candy_sack.count(kind)));
+        push_line(
+            &mut sections,
+            format!("{}Sack {}/{}:", label(player), candy_sack.used(), candy_sack.capacity),
+            &counts,
+        );
+    }
+
+    // The shared inventory, which is player one's once anyone banks into their own
+    let shared_label = if ghosts.iter().any(|(_, _, own_inventory)| own_inventory.is_some()) {
+        "P1 Collected:"
+    } else {
+        "Collected:"
+    };
+    push_line(&mut sections, shared_label.to_string(), &collected_counts(&inventory));
+    push_spooks(&mut sections, inventory.spooks);
+    for (player, _, own_inventory) in &ghosts {
+        if let Some(own_inventory) = own_inventory {
+            push_line(
+                &mut sections,
+                format!("{}Collected:", label(player)),
+                &collected_counts(own_inventory),
+            );
+            push_spooks(&mut sections, own_inventory.spooks);
+        }
+    }
+
+    text.sections = sections;
+}
+
+// Spook points from pranks, see `pranks`
+fn push_spooks(sections: &mut Vec<TextSection>, spooks: u32) {
+    if spooks > 0 {
+        sections.push(TextSection::new(
+            format!("  {} spook{}", spooks, if spooks == 1 { "" } else { "s" }),
+            hud_style(SPOOK_COLOR),
+        ));
+    }
+}
+
+fn hud_style(color: Color) -> TextStyle {
+    TextStyle {
+        font_size: 16.0,
+        color,
+        ..default()
+    }
+}
+
+fn collected_counts(inventory: &PlayerInventory) -> [(CandyKind, u32); 5] {
+    CANDY_KINDS.map(|kind| (kind, inventory.collected.get(&kind).copied().unwrap_or(0)))
+}
+
+// One HUD line, each kind in its own colour
+fn push_line(sections: &mut Vec<TextSection>, title: String, counts: &[(CandyKind, u32)]) {
+    let style = hud_style;
+
+    if !sections.is_empty() {
+        sections.push(TextSection::new("\n", style(Color::WHITE)));
+    }
+    sections.push(TextSection::new(title, style(Color::WHITE)));
+
+    let mut any = false;
+    for (kind, count) in counts.iter().filter(|(_, count)| *count > 0) {
+        sections.push(TextSection::new(format!("  {} {}", count, kind.name()), style(kind.color())));
+        any = true;
+    }
+    if !any {
+        sections.push(TextSection::new("  nothing", style(Color::srgb(0.6, 0.6, 0.6))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sack_fills_by_slots() {
+        let mut sack = CandySack::new(4);
+        assert!(sack.add(CandyKind::FullSizeBar, 4));
+        assert_eq!(sack.free(), 1);
+        assert!(!sack.add(CandyKind::RottenApple, 0));
+        assert!(sack.add(CandyKind::Lollipop, 1));
+
+        assert!(sack.is_full());
+        assert_eq!(sack.len(), 2);
+        assert_eq!(sack.points(), 5);
+        assert_eq!(sack.load(), 1.0);
+    }
+
+    #[test]
+    fn cursed_candy_costs_points() {
+        let mut sack = CandySack::new(10);
+        sack.add(CandyKind::Chocolate, 2);
+        sack.add(CandyKind::CursedCandy, -2);
+        assert_eq!(sack.points(), 0);
+    }
+
+    #[test]
+    fn spill_takes_the_newest_with_what_they_banked() {
+        let mut sack = CandySack::new(10);
+        sack.add(CandyKind::Chocolate, 2);
+        sack.add(CandyKind::Lollipop, 1);
+        sack.add(CandyKind::CursedCandy, 0);
+
+        let spilled = sack.spill(2);
+        assert_eq!(spilled, vec![(CandyKind::Lollipop, 1), (CandyKind::CursedCandy, 0)]);
+        assert_eq!(sack.count(CandyKind::Chocolate), 1);
+
+        assert_eq!(sack.spill(5).len(), 1);
+        assert!(sack.is_empty());
+    }
+
+    #[test]
+    fn draw_only_picks_what_fits() {
+        let table = LootTable {
+            entries: vec![(CandyKind::FullSizeBar, 100), (CandyKind::Lollipop, 1)],
+        };
+        for _ in 0..50 {
+            assert_eq!(table.draw(1), Some(CandyKind::Lollipop));
+        }
+        assert_eq!(table.draw(0), None);
+    }
+
+    #[test]
+    fn draw_nothing_when_nothing_can_come_out() {
+        let too_big = LootTable {
+            entries: vec![(CandyKind::FullSizeBar, 1)],
+        };
+        assert_eq!(too_big.draw(2), None);
+
+        let no_chance = LootTable {
+            entries: vec![(CandyKind::Chocolate, 0)],
+        };
+        assert_eq!(no_chance.draw(10), None);
+    }
+
+    #[test]
+    fn loot_table_reads_as_a_list() {
+        let table: LootTable = ron::from_str("[(Chocolate, 3), (RottenApple, 1)]").unwrap();
+        assert_eq!(table.entries, vec![(CandyKind::Chocolate, 3), (CandyKind::RottenApple, 1)]);
+        assert_eq!(LootTable::default().draw(1), Some(CandyKind::Lollipop));
+    }
+}
@@ -0,0 +1,223 @@
+// Costumes for the ghost. Each one brings its own sprite sheet, laid out like
+// `animation::Sheet::Ghost` with normal and Faded frames, and a trail tint. F2
+// opens the wardrobe, which pauses the game; the costume worn is kept in
+// profile.json. Costumes are cosmetic only and stay on this machine, so in a
+// LAN game each side sees its own choice on player one's ghost.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    achievements::{Achievement, Profile},
+    GameState, Player,
+};
+
+const COSTUMES: [Costume; 3] = [Costume::Sheet, Costume::Vampire, Costume::PumpkinHead];
+
+pub struct CostumePlugin;
+
+impl Plugin for CostumePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wardrobe>()
+            .add_systems(
+                Update,
+                (
+                    toggle_wardrobe,
+                    navigate_wardrobe.run_if(in_state(GameState::Wardrobe)),
+                    update_wardrobe_text.run_if(in_state(GameState::Wardrobe)),
+                    wear_profile_costume,
+                    dress_ghosts,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Costume {
+    #[default]
+    Sheet,
+    Vampire,
+    PumpkinHead,
+}
+
+impl Costume {
+    fn name(self) -> &'static str {
+        match self {
+            Costume::Sheet => "Sheet Ghost",
+            Costume::Vampire => "Vampire Ghost",
+            Costume::PumpkinHead => "Pumpkin-head Ghost",
+        }
+    }
+
+    pub fn sheet(self) -> &'static str {
+        match self {
+            Costume::Sheet => "sprites/sheets/ghost.png",
+            Costume::Vampire => "sprites/sheets/vampire.png",
+            Costume::PumpkinHead => "sprites/sheets/pumpkin_head.png",
+        }
+    }
+
+    pub fn trail_tint(self) -> Color {
+        match self {
+            Costume::Sheet => Color::WHITE,
+            Costume::Vampire => Color::srgb(0.7, 0.1, 0.2),
+            Costume::PumpkinHead => Color::srgb(1.0, 0.55, 0.1),
+        }
+    }
+
+    // The tint on top of the ghost's own colour, so co-op trails stay apart
+    pub fn trail_color(self, ghost_color: Color) -> Color {
+        let tinted = ghost_color.to_linear().to_vec4() * self.trail_tint().to_linear().to_vec4();
+        LinearRgba::from_vec4(tinted).into()
+    }
+
+    // The achievement that unlocks it, None for the costumes everyone starts with
+    fn unlocked_by(self) -> Option<Achievement> {
+        match self {
+            Costume::Sheet => None,
+            Costume::Vampire => Some(Achievement::Unseen),
+            Costume::PumpkinHead => Some(Achievement::GrandTour),
+        }
+    }
+
+    fn is_unlocked(self, profile: &Profile) -> bool {
+        self.unlocked_by()
+            .is_none_or(|achievement| profile.unlocked.contains(&achievement))
+    }
+}
+
+#[derive(Resource, Default)]
+struct Wardrobe {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct WardrobeRoot;
+
+#[derive(Component)]
+struct WardrobeText;
+
+fn toggle_wardrobe(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut wardrobe: ResMut<Wardrobe>,
+    profile: Res<Profile>,
+    wardrobe_query: Query<Entity, With<WardrobeRoot>>,
+) {
+    let open = *state.get() == GameState::Wardrobe;
+    let close_pressed = keyboard.just_pressed(KeyCode::F2) || keyboard.just_pressed(KeyCode::Escape);
+
+    if open && close_pressed {
+        for entity in wardrobe_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        next_state.set(GameState::Playing);
+    } else if *state.get() == GameState::Playing && keyboard.just_pressed(KeyCode::F2) {
+        // Start on whatever is being worn
+        wardrobe.selected = COSTUMES
+            .iter()
+            .position(|costume| *costume == profile.costume)
+            .unwrap_or(0);
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                    z_index: ZIndex::Global(100),
+                    ..default()
+                },
+                WardrobeRoot,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    WardrobeText,
+                ));
+            });
+        next_state.set(GameState::Wardrobe);
+    }
+}
+
+fn navigate_wardrobe(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut wardrobe: ResMut<Wardrobe>,
+    mut profile: ResMut<Profile>,
+) {
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        wardrobe.selected = (wardrobe.selected + COSTUMES.len() - 1) % COSTUMES.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        wardrobe.selected = (wardrobe.selected + 1) % COSTUMES.len();
+    }
+
+    let costume = COSTUMES[wardrobe.selected];
+    if keyboard.just_pressed(KeyCode::Enter) && costume.is_unlocked(&profile) && profile.costume != costume {
+        profile.costume = costume;
+    }
+}
+
+fn update_wardrobe_text(
+    wardrobe: Res<Wardrobe>,
+    profile: Res<Profile>,
+    mut text_query: Query<&mut Text, With<WardrobeText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        let mut lines = vec!["WARDROBE".to_string(), String::new()];
+        for (index, costume) in COSTUMES.iter().enumerate() {
+            let marker = if index == wardrobe.selected { "> " } else { "  " };
+            let status = if *costume == profile.costume {
+                "wearing".to_string()
+            } else if costume.is_unlocked(&profile) {
+                "unlocked".to_string()
+            } else {
+                // unlocked_by is always Some for a locked costume
+                let achievement = costume.unlocked_by().map_or("", |achievement| achievement.title());
+                format!("locked, unlock {}", achievement)
+            };
+            lines.push(format!("{}{} ({})", marker, costume.name(), status));
+        }
+        lines.push(String::new());
+        lines.push("Up/Down: select   Enter: wear   F2/Esc: close".to_string());
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+// Player one wears the saved costume, anyone else keeps the plain sheet
+fn wear_profile_costume(
+    profile: Res<Profile>,
+    mut ghost_query: Query<(Ref<Player>, &mut Costume)>,
+) {
+    for (player, mut costume) in ghost_query.iter_mut() {
+        if player.id == 0 && (profile.is_changed() || player.is_added()) && *costume != profile.costume {
+            *costume = profile.costume;
+        }
+    }
+}
+
+// Swaps the sheet, the animation carries on from the same frame
+fn dress_ghosts(
+    asset_server: Res<AssetServer>,
+    mut ghost_query: Query<(&mut Handle<Image>, &Costume), Changed<Costume>>,
+) {
+    for (mut texture, costume) in ghost_query.iter_mut() {
+        *texture = asset_server.load(costume.sheet());
+    }
+}
@@ -0,0 +1,89 @@
+// Debug gizmos, only built with `--features debug`. F4 draws the ranges the
+// game checks distances against: where ghosts can trick-or-treat and deposit,
+// how close a bullet has to get to the balloon, where bullets are cleaned up,
+// plus each bullet's heading and every house's light and treat timer.
+
+use bevy::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+use crate::{
+    shop::Upgrades, tuning::GameTuning, BalloonPumpkin, Bullet, House, Pumpkin, BULLET_DESPAWN_DISTANCE, BULLET_HIT_RANGE,
+    DEPOSIT_RANGE,
+};
+
+// How far ahead a bullet's ray reaches
+const BULLET_RAY_SECONDS: f32 = 0.1;
+
+pub struct DebugGizmosPlugin;
+
+impl Plugin for DebugGizmosPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<DebugGizmos>()
+            .add_systems(Startup, hide_debug_gizmos)
+            .add_systems(Update, (toggle_debug_gizmos, draw_debug_gizmos).chain());
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+struct DebugGizmos;
+
+fn hide_debug_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    config_store.config_mut::<DebugGizmos>().0.enabled = false;
+}
+
+fn toggle_debug_gizmos(keyboard: Res<ButtonInput<KeyCode>>, mut config_store: ResMut<GizmoConfigStore>) {
+    if keyboard.just_pressed(KeyCode::F4) {
+        let (config, _) = config_store.config_mut::<DebugGizmos>();
+        config.enabled = !config.enabled;
+    }
+}
+
+fn draw_debug_gizmos(
+    mut gizmos: Gizmos<DebugGizmos>,
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
+    house_query: Query<(&Transform, &House)>,
+    pumpkin_query: Query<&Transform, With<Pumpkin>>,
+    balloon_query: Query<&Transform, With<BalloonPumpkin>>,
+    bullet_query: Query<(&Transform, &Bullet)>,
+) {
+    let interaction_range = upgrades.interaction_range(&tuning);
+
+    for (transform, house) in house_query.iter() {
+        let position = transform.translation.truncate();
+        let color = match (house.light_status, house.lights_out) {
+            (true, false) => Color::srgb(1.0, 0.9, 0.3),
+            (true, true) => Color::srgb(1.0, 0.5, 0.1),
+            (false, _) => Color::srgb(0.4, 0.4, 0.5),
+        };
+        gizmos.circle_2d(position, interaction_range, color);
+
+        // The treat timer fills clockwise from the top, inside the range
+        let fraction = house.interaction_timer.fraction();
+        if fraction > 0.0 {
+            gizmos
+                .arc_2d(position, -fraction * PI, fraction * TAU, interaction_range - 8.0, Color::srgb(0.3, 1.0, 0.4))
+                .resolution(48);
+        }
+    }
+
+    for transform in pumpkin_query.iter() {
+        gizmos.circle_2d(transform.translation.truncate(), DEPOSIT_RANGE, Color::srgb(1.0, 0.5, 0.0));
+    }
+
+    for transform in balloon_query.iter() {
+        gizmos.circle_2d(transform.translation.truncate(), BULLET_HIT_RANGE, Color::srgb(1.0, 0.2, 0.2));
+    }
+
+    gizmos
+        .circle_2d(Vec2::ZERO, BULLET_DESPAWN_DISTANCE, Color::srgb(0.6, 0.2, 0.8))
+        .resolution(128);
+
+    for (transform, bullet) in bullet_query.iter() {
+        gizmos.ray_2d(
+            transform.translation.truncate(),
+            bullet.direction * bullet.speed * BULLET_RAY_SECONDS,
+            Color::srgb(1.0, 0.2, 0.2),
+        );
+    }
+}
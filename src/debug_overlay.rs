@@ -0,0 +1,146 @@
+// F3 debug overlay: frame rate, how many of the short-lived entities are
+// around, each ghost's state and sack, and every house's timers. Handy when
+// something looks off and the logs aren't enough.
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use std::fmt::Write;
+
+use crate::{
+    level::LightSchedule, net::NetId, particles::Particle, tuning::GameTuning, Bullet, CandySack, Ghost, GhostState,
+    GhostTrail, House, Player,
+};
+
+type GhostDebugQuery<'w, 's> = Query<'w, 's, (&'static Player, &'static Ghost, &'static CandySack)>;
+type HouseDebugQuery<'w, 's> = Query<'w, 's, (&'static NetId, &'static House, Option<&'static LightSchedule>)>;
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .add_systems(Update, (toggle_debug_overlay, update_debug_overlay).chain());
+    }
+}
+
+#[derive(Component)]
+struct DebugOverlay;
+
+fn toggle_debug_overlay(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    overlay_query: Query<Entity, With<DebugOverlay>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    if overlay_query.is_empty() {
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 16.0,
+                    color: Color::srgb(0.6, 1.0, 0.6),
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            })
+            .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            ZIndex::Global(90),
+            DebugOverlay,
+        ));
+    } else {
+        for entity in overlay_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_debug_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    tuning: Res<GameTuning>,
+    particle_query: Query<&Particle>,
+    trail_query: Query<(), With<GhostTrail>>,
+    bullet_query: Query<(), With<Bullet>>,
+    entity_query: Query<Entity>,
+    ghost_query: GhostDebugQuery,
+    house_query: HouseDebugQuery,
+    mut overlay_query: Query<&mut Text, With<DebugOverlay>>,
+) {
+    let Ok(mut text) = overlay_query.get_single_mut() else {
+        return;
+    };
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let live_particles = particle_query.iter().filter(|particle| particle.is_alive()).count();
+
+    // Writing to a String can't fail
+    let mut out = String::new();
+    let _ = writeln!(out, "FPS: {:.0}", fps);
+    let _ = writeln!(out, "Entities: {}", entity_query.iter().count());
+    let _ = writeln!(
+        out,
+        "Particles: {} live, {} pooled",
+        live_particles,
+        particle_query.iter().count() - live_particles
+    );
+    let _ = writeln!(out, "Trails: {}  Bullets: {}", trail_query.iter().count(), bullet_query.iter().count());
+
+    let mut ghosts: Vec<_> = ghost_query.iter().collect();
+    ghosts.sort_by_key(|(player, ..)| player.id);
+    for (player, ghost, candy_sack) in ghosts {
+        let state = match ghost.state {
+            GhostState::Normal => "Normal",
+            GhostState::Faded => "Faded",
+        };
+        let _ = writeln!(
+            out,
+            "Player {}: {}, sack {}/{} slots, {} candies worth {}",
+            player.id + 1,
+            state,
+            candy_sack.used(),
+            candy_sack.capacity,
+            candy_sack.len(),
+            candy_sack.points()
+        );
+    }
+
+    let mut houses: Vec<_> = house_query.iter().collect();
+    houses.sort_by_key(|(net_id, ..)| net_id.0);
+    for (net_id, house, schedule) in houses {
+        let lights = match (house.light_status, house.lights_out) {
+            (true, true) => "going out",
+            (true, false) => "lit",
+            (false, _) => "dark",
+        };
+        let until_dark = schedule
+            .and_then(LightSchedule::time_until_dark)
+            .map(|seconds| format!(", dark in {:.1}s", seconds / tuning.light_pace))
+            .unwrap_or_default();
+        let _ = writeln!(
+            out,
+            "House {}: {}{}, treat {:.0}%, {} rival timers, prank {}",
+            net_id.0 + 1,
+            lights,
+            until_dark,
+            house.interaction_timer.fraction() * 100.0,
+            house.rival_timers.len(),
+            if house.pranked { "done".to_string() } else { format!("{:.0}%", house.prank_timer.fraction() * 100.0) }
+        );
+    }
+
+    text.sections[0].value = out.trim_end().to_string();
+}
@@ -0,0 +1,258 @@
+// Difficulty presets. Picked on the New Game screen when the game starts, or
+// up front with `--difficulty easy|normal|nightmare`, and stored in the save
+// file, so a loaded run keeps the difficulty it was started with. LAN games
+// skip the screen and play on Normal unless the flag says otherwise.
+//
+// A preset scales the values from the tuning file, see `tuning`, and shop
+// upgrades build on top of the result.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{tuning::GameTuning, GameState};
+
+const DIFFICULTIES: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Nightmare];
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NewGameMenu>()
+            .add_systems(Startup, open_new_game_menu)
+            .add_systems(
+                Update,
+                (navigate_new_game_menu, update_new_game_text)
+                    .chain()
+                    .run_if(in_state(GameState::NewGame)),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Nightmare,
+}
+
+impl Difficulty {
+    // None if it wasn't given, so the New Game screen asks
+    pub fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let name = args
+            .iter()
+            .position(|arg| arg == "--difficulty")
+            .and_then(|index| args.get(index + 1));
+
+        match name.map(|name| name.to_lowercase()).as_deref() {
+            None => None,
+            Some("normal") => Some(Difficulty::Normal),
+            Some("easy") => Some(Difficulty::Easy),
+            Some("nightmare") => Some(Difficulty::Nightmare),
+            Some(other) => {
+                warn!("Unknown difficulty {:?}, playing on Normal", other);
+                Some(Difficulty::Normal)
+            }
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Nightmare => "Nightmare",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Bigger sacks, slower lights, forgiving residents",
+            Difficulty::Normal => "The night as it was meant to be",
+            Difficulty::Nightmare => "Small sacks, restless lights, residents who fight back",
+        }
+    }
+
+    pub fn adjust(self, tuning: &mut GameTuning) {
+        // (sack, progress per sack, light pace, trick-or-treat time, balloon speed, sack weight, aggression)
+        let (sack, progress, light_pace, interaction, balloon, weight, aggression) = match self {
+            Difficulty::Easy => (1.5, 1.4, 0.75, 0.7, 0.7, 0.5, 0.5),
+            Difficulty::Normal => return,
+            Difficulty::Nightmare => (0.7, 0.7, 1.4, 1.4, 1.6, 1.5, 1.6),
+        };
+
+        tuning.sack_capacity = (tuning.sack_capacity as f32 * sack).round() as u32;
+        tuning.progress_per_full_sack *= progress;
+        tuning.light_pace *= light_pace;
+        tuning.interaction_seconds *= interaction;
+        tuning.balloon_float_speed *= balloon;
+        // Bounds are left to `GameTuning::clamp`
+        tuning.full_sack_slowdown *= weight;
+        tuning.full_sack_float_damping *= weight;
+        tuning.aggression *= aggression;
+    }
+}
+
+#[derive(Resource, Default)]
+struct NewGameMenu {
+    selected: usize,  // Index into DIFFICULTIES
+}
+
+#[derive(Component)]
+struct NewGameRoot;
+
+#[derive(Component)]
+struct NewGameText;
+
+fn open_new_game_menu(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    difficulty: Res<Difficulty>,
+    mut menu: ResMut<NewGameMenu>,
+) {
+    if *state.get() != GameState::NewGame {
+        return;
+    }
+
+    menu.selected = DIFFICULTIES.iter().position(|preset| preset == &*difficulty).unwrap_or(1);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.85).into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            NewGameRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                NewGameText,
+            ));
+        });
+}
+
+fn navigate_new_game_menu(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<NewGameMenu>,
+    mut difficulty: ResMut<Difficulty>,
+    mut next_state: ResMut<NextState<GameState>>,
+    root_query: Query<Entity, With<NewGameRoot>>,
+) {
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + DIFFICULTIES.len() - 1) % DIFFICULTIES.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % DIFFICULTIES.len();
+    }
+
+    if keyboard.just_pressed(KeyCode::Enter) {
+        difficulty.set_if_neq(DIFFICULTIES[menu.selected]);
+        for entity in root_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        info!("New game on {:?}", DIFFICULTIES[menu.selected]);
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn update_new_game_text(
+    menu: Res<NewGameMenu>,
+    mut text_query: Query<&mut Text, With<NewGameText>>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+
+    for mut text in text_query.iter_mut() {
+        let mut lines = vec!["NEW GAME".to_string(), String::new()];
+        for (index, preset) in DIFFICULTIES.iter().enumerate() {
+            let marker = if index == menu.selected { "> " } else { "  " };
+            lines.push(format!("{}{}: {}", marker, preset.name(), preset.description()));
+        }
+        lines.push(String::new());
+        lines.push("Up/Down: select   Enter: start".to_string());
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The way `apply_tuning` builds it
+    fn applied(difficulty: Difficulty, mut tuning: GameTuning) -> GameTuning {
+        difficulty.adjust(&mut tuning);
+        tuning.clamp();
+        tuning
+    }
+
+    fn adjusted(difficulty: Difficulty) -> GameTuning {
+        applied(difficulty, GameTuning::default())
+    }
+
+    #[test]
+    fn normal_keeps_the_tuning() {
+        let normal = ron::to_string(&adjusted(Difficulty::Normal)).unwrap();
+        assert_eq!(normal, ron::to_string(&GameTuning::default()).unwrap());
+    }
+
+    #[test]
+    fn easy_and_nightmare_pull_opposite_ways() {
+        let base = GameTuning::default();
+        let easy = adjusted(Difficulty::Easy);
+        let nightmare = adjusted(Difficulty::Nightmare);
+
+        assert!(easy.sack_capacity > base.sack_capacity && nightmare.sack_capacity < base.sack_capacity);
+        assert!(easy.interaction_seconds < base.interaction_seconds);
+        assert!(nightmare.interaction_seconds > base.interaction_seconds);
+        assert!(easy.aggression < base.aggression && nightmare.aggression > base.aggression);
+        assert!(easy.full_sack_slowdown < base.full_sack_slowdown);
+        assert!(nightmare.full_sack_slowdown > base.full_sack_slowdown);
+    }
+
+    #[test]
+    fn nightmare_stays_in_bounds() {
+        let tuning = applied(Difficulty::Nightmare, GameTuning {
+            sack_capacity: 1,
+            full_sack_slowdown: 0.8,
+            full_sack_float_damping: 0.9,
+            ..default()
+        });
+
+        assert_eq!(tuning.sack_capacity, 1);
+        assert_eq!(tuning.full_sack_slowdown, 0.9);
+        assert_eq!(tuning.full_sack_float_damping, 1.0);
+    }
+
+    #[test]
+    fn normal_stays_in_bounds() {
+        let tuning = applied(Difficulty::Normal, GameTuning {
+            sack_capacity: 0,
+            full_sack_slowdown: 1.5,
+            full_sack_float_damping: 2.0,
+            ..default()
+        });
+
+        assert_eq!(tuning.sack_capacity, 1);
+        assert_eq!(tuning.full_sack_slowdown, 0.9);
+        assert_eq!(tuning.full_sack_float_damping, 1.0);
+    }
+}
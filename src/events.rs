@@ -0,0 +1,127 @@
+// Gameplay events. The systems that make things happen send these, and anything
+// that only wants to react (sound, particles, achievements, stats) reads them
+// instead of being wired into those systems.
+
+use bevy::prelude::*;
+
+use crate::candy::CandyKind;
+
+pub struct GameEventsPlugin;
+
+impl Plugin for GameEventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CandyCollected>()
+            .add_event::<CandyDeposited>()
+            .add_event::<BalloonPopped>()
+            .add_event::<ShotFired>()
+            .add_event::<SackFull>()
+            .add_event::<HouseLightChanged>()
+            .add_event::<NightStarted>()
+            .add_event::<HousePranked>()
+            .add_systems(Update, log_gameplay_events);
+    }
+}
+
+// A ghost got a piece of candy, from a house or off the ground
+#[derive(Event)]
+pub struct CandyCollected {
+    pub player: usize,
+    pub house: Option<Entity>,  // None for candy knocked out of a rival's sack
+    pub kind: CandyKind,
+    pub position: Vec3,
+}
+
+// A ghost emptied its sack into the pumpkin
+#[derive(Event)]
+pub struct CandyDeposited {
+    pub player: usize,
+    pub amount: u32,  // Pieces of candy
+    pub slots: u32,  // Sack slots they took up
+    pub capacity: u32,
+    pub position: Vec3,
+}
+
+#[derive(Event)]
+pub struct BalloonPopped {
+    pub position: Vec3,
+}
+
+// A ghost fired at the balloon
+#[derive(Event)]
+pub struct ShotFired {
+    pub player: usize,
+    pub position: Vec3,
+}
+
+// A ghost's sack just reached capacity
+#[derive(Event)]
+pub struct SackFull {
+    pub player: usize,
+}
+
+// A prank on a dark house landed
+#[derive(Event)]
+pub struct HousePranked {
+    pub player: usize,
+    pub house: Entity,
+    pub position: Vec3,
+    pub woke_up: bool,  // The residents switched the lights back on
+}
+
+#[derive(Event)]
+pub struct HouseLightChanged {
+    pub house: Entity,
+    pub lit: bool,
+}
+
+// The shop closed and a fresh night begins
+#[derive(Event)]
+pub struct NightStarted;
+
+// Shows up with RUST_LOG=debug, handy when checking what a change fires
+#[allow(clippy::too_many_arguments)]
+fn log_gameplay_events(
+    mut collected_events: EventReader<CandyCollected>,
+    mut deposited_events: EventReader<CandyDeposited>,
+    mut popped_events: EventReader<BalloonPopped>,
+    mut shot_events: EventReader<ShotFired>,
+    mut sack_full_events: EventReader<SackFull>,
+    mut light_events: EventReader<HouseLightChanged>,
+    mut night_events: EventReader<NightStarted>,
+    mut prank_events: EventReader<HousePranked>,
+) {
+    for event in collected_events.read() {
+        match event.house {
+            Some(house) => debug!("Player {} collected {:?} at house {:?}", event.player + 1, event.kind, house),
+            None => debug!("Player {} picked up dropped {:?}", event.player + 1, event.kind),
+        }
+    }
+    for event in deposited_events.read() {
+        debug!(
+            "Player {} deposited {} candies ({}/{} slots)",
+            event.player + 1,
+            event.amount,
+            event.slots,
+            event.capacity
+        );
+    }
+    for event in popped_events.read() {
+        debug!("Balloon popped at {}", event.position.truncate());
+    }
+    for event in shot_events.read() {
+        debug!("Player {} fired at the balloon", event.player + 1);
+    }
+    for event in sack_full_events.read() {
+        debug!("Player {}'s sack is full", event.player + 1);
+    }
+    for event in light_events.read() {
+        debug!("House {:?} lights {}", event.house, if event.lit { "on" } else { "off" });
+    }
+    for _ in night_events.read() {
+        debug!("A new night started");
+    }
+    for event in prank_events.read() {
+        let outcome = if event.woke_up { "woke the residents" } else { "got away with it" };
+        debug!("Player {} pranked house {:?} and {}", event.player + 1, event.house, outcome);
+    }
+}
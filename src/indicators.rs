@@ -0,0 +1,236 @@
+// In-world indicators above each house: a ring that fills while a ghost
+// trick-or-treats (or pranks a dark house), a candy that pops out when it's
+// done, and a moon while the house is dark. They sit above the darkness layer
+// so they're always readable.
+
+use bevy::prelude::*;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::{events::CandyCollected, settings::Settings, GameState, House};
+
+const RING_SEGMENTS: usize = 24;
+const RING_RADIUS: f32 = 22.0;
+
+// Above the house and above the darkness, see `lighting`
+const INDICATOR_OFFSET: Vec3 = Vec3::new(0.0, 110.0, 7.0);
+
+const CANDY_POP_SECONDS: f32 = 0.7;
+
+const PRANK_FILL: Color = Color::srgb(0.65, 0.4, 1.0);
+
+pub struct IndicatorsPlugin;
+
+impl Plugin for IndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                despawn_house_indicators,
+                spawn_house_indicators,
+                update_progress_rings,
+                update_dark_markers,
+                pop_candy.run_if(in_state(GameState::Playing)),
+                animate_candy_pops.run_if(in_state(GameState::Playing)),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ProgressRing {
+    house: Entity,
+}
+
+#[derive(Component)]
+struct RingSegment {
+    index: usize,  // Clockwise from the top
+}
+
+#[derive(Component)]
+struct DarkMarker {
+    house: Entity,
+}
+
+#[derive(Component)]
+struct CandyPop {
+    timer: Timer,
+    origin: Vec3,
+    color: Color,  // Tinted by the kind of candy
+}
+
+// Houses go when the level file is reloaded, see `spawn_houses`
+fn despawn_house_indicators(
+    mut commands: Commands,
+    mut removed_houses: RemovedComponents<House>,
+    ring_query: Query<(Entity, &ProgressRing)>,
+    marker_query: Query<(Entity, &DarkMarker)>,
+) {
+    for house in removed_houses.read() {
+        let rings = ring_query.iter().filter(|(_, ring)| ring.house == house).map(|(entity, _)| entity);
+        let markers = marker_query.iter().filter(|(_, marker)| marker.house == house).map(|(entity, _)| entity);
+        for entity in rings.chain(markers) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_house_indicators(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    house_query: Query<(Entity, &Transform), Added<House>>,
+) {
+    for (house, transform) in house_query.iter() {
+        let position = transform.translation + INDICATOR_OFFSET;
+
+        commands
+            .spawn((
+                SpatialBundle {
+                    transform: Transform::from_translation(position),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ProgressRing { house },
+            ))
+            .with_children(|parent| {
+                for index in 0..RING_SEGMENTS {
+                    let angle = FRAC_PI_2 - TAU * index as f32 / RING_SEGMENTS as f32;
+                    parent.spawn((
+                        SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(Vec2::new(4.0, 9.0)),
+                                ..default()
+                            },
+                            // Each segment points out from the centre
+                            transform: Transform::from_translation((Vec2::from_angle(angle) * RING_RADIUS).extend(0.0))
+                                .with_rotation(Quat::from_rotation_z(angle - FRAC_PI_2)),
+                            ..default()
+                        },
+                        RingSegment { index },
+                    ));
+                }
+            });
+
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/icons/moon.png"),
+                transform: Transform::from_translation(position).with_scale(Vec3::splat(0.5)),
+                sprite: Sprite {
+                    color: Color::WHITE.with_alpha(0.8),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            DarkMarker { house },
+        ));
+    }
+}
+
+fn update_progress_rings(
+    settings: Res<Settings>,
+    house_query: Query<&House>,
+    mut ring_query: Query<(&ProgressRing, &mut Visibility, &Children)>,
+    mut segment_query: Query<(&RingSegment, &mut Sprite)>,
+) {
+    let palette = settings.palette();
+
+    for (ring, mut visibility, children) in ring_query.iter_mut() {
+        let Ok(house) = house_query.get(ring.house) else {
+            continue;
+        };
+
+        // Versus shows whoever is furthest along, dark houses show the prank
+        let (fraction, fill) = if house.light_status {
+            (house.visit_fraction(), palette.progress_fill())
+        } else {
+            (house.prank_timer.fraction(), PRANK_FILL)
+        };
+
+        let shown = fraction > 0.0 && fraction < 1.0;
+        visibility.set_if_neq(if shown { Visibility::Inherited } else { Visibility::Hidden });
+        if !shown {
+            continue;
+        }
+
+        let filled = (fraction * RING_SEGMENTS as f32).ceil() as usize;
+        for child in children.iter() {
+            if let Ok((segment, mut sprite)) = segment_query.get_mut(*child) {
+                sprite.color = if segment.index < filled {
+                    fill
+                } else {
+                    Color::srgba(1.0, 1.0, 1.0, 0.25)
+                };
+            }
+        }
+    }
+}
+
+fn update_dark_markers(
+    house_query: Query<&House>,
+    mut marker_query: Query<(&DarkMarker, &mut Visibility)>,
+) {
+    for (marker, mut visibility) in marker_query.iter_mut() {
+        if let Ok(house) = house_query.get(marker.house) {
+            visibility.set_if_neq(if house.light_status { Visibility::Hidden } else { Visibility::Inherited });
+        }
+    }
+}
+
+fn pop_candy(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut collected_events: EventReader<CandyCollected>,
+    house_query: Query<&Transform, With<House>>,
+) {
+    for event in collected_events.read() {
+        let Some(Ok(house_transform)) = event.house.map(|house| house_query.get(house)) else {
+            continue;
+        };
+
+        let origin = house_transform.translation + INDICATOR_OFFSET;
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("sprites/icons/candy.png"),
+                transform: Transform::from_translation(origin).with_scale(Vec3::ZERO),
+                sprite: Sprite {
+                    color: event.kind.color(),
+                    ..default()
+                },
+                ..default()
+            },
+            CandyPop {
+                timer: Timer::from_seconds(CANDY_POP_SECONDS, TimerMode::Once),
+                origin,
+                color: event.kind.color(),
+            },
+        ));
+    }
+}
+
+// Springs up past full size, settles, then drifts up and fades
+fn animate_candy_pops(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pop_query: Query<(Entity, &mut CandyPop, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut pop, mut transform, mut sprite) in pop_query.iter_mut() {
+        pop.timer.tick(time.delta());
+        if pop.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let t = pop.timer.fraction();
+        let scale = if t < 0.2 {
+            1.3 * t / 0.2
+        } else if t < 0.35 {
+            1.3 - 0.3 * (t - 0.2) / 0.15
+        } else {
+            1.0
+        };
+        transform.scale = Vec3::splat(0.6 * scale);
+        transform.translation = pop.origin + Vec3::Y * 30.0 * t;
+        sprite.color = pop.color.with_alpha(((1.0 - t) * 2.0).min(1.0));
+    }
+}
@@ -0,0 +1,299 @@
+// Level data: where the houses stand, when their residents switch the lights
+// on and off and what candy they hand out. Read from
+// assets/levels/street.level.ron through the asset server, and the street is
+// rebuilt whenever the file changes, see `spawn_houses`.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{candy::LootTable, HouseType};
+
+const LEVEL_PATH: &str = "levels/street.level.ron";
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .register_asset_loader(LevelLoader)
+            .init_resource::<LevelHandle>();
+    }
+}
+
+#[derive(Asset, TypePath, Deserialize)]
+pub struct Level {
+    pub houses: Vec<HouseSpawn>,
+    patterns: HashMap<HouseType, LightPattern>,
+    #[serde(default)]
+    loot: HashMap<HouseType, LootTable>,  // Lollipops only for any type left out
+}
+
+#[derive(Deserialize)]
+pub struct HouseSpawn {
+    pub position: (f32, f32),
+    pub kind: HouseType,
+    #[serde(default)]
+    offset: f32,  // Seconds into the pattern the house starts at
+}
+
+#[derive(Deserialize, Clone, Default)]
+struct LightPattern {
+    cycle: Vec<(f32, f32)>,  // (lit, dark) seconds, repeating; empty means always lit
+    #[serde(default)]
+    bedtime: Option<f32>,  // Seconds into the night after which the lights stay off
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "could not read level: {}", error),
+            LevelError::Ron(error) => write!(f, "invalid level RON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = LevelError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Level, LevelError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(LevelError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(LevelError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+// Keeps the asset loaded so it can be watched
+#[derive(Resource)]
+struct LevelHandle(Handle<Level>);
+
+impl FromWorld for LevelHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load(LEVEL_PATH))
+    }
+}
+
+// The level, whenever the file has just (re)loaded
+#[derive(SystemParam)]
+pub struct LevelData<'w, 's> {
+    asset_events: EventReader<'w, 's, AssetEvent<Level>>,
+    handle: Res<'w, LevelHandle>,
+    levels: Res<'w, Assets<Level>>,
+}
+
+impl LevelData<'_, '_> {
+    pub fn reloaded(&mut self) -> Option<&Level> {
+        let handle = &self.handle.0;
+        let reloaded = self
+            .asset_events
+            .read()
+            .filter(|event| event.is_loaded_with_dependencies(handle) || event.is_modified(handle))
+            .count()
+            > 0;
+        if !reloaded {
+            return None;
+        }
+
+        info!("Level {} loaded", LEVEL_PATH);
+        self.levels.get(handle)
+    }
+}
+
+impl Level {
+    pub fn schedule(&self, house: &HouseSpawn) -> LightSchedule {
+        let pattern = self.patterns.get(&house.kind).cloned().unwrap_or_default();
+        LightSchedule::new(pattern, house.offset)
+    }
+
+    pub fn loot(&self, house: &HouseSpawn) -> LootTable {
+        self.loot.get(&house.kind).cloned().unwrap_or_default()
+    }
+}
+
+// Where a house is in its light pattern. Only the authority ticks these, clients
+// get the lights from snapshots.
+#[derive(Component)]
+pub struct LightSchedule {
+    pattern: LightPattern,
+    offset: f32,
+    step: usize,
+    lit: bool,
+    phase_left: f32,  // Seconds until the lights next switch
+    night: f32,       // Seconds into the night
+}
+
+impl LightSchedule {
+    fn new(pattern: LightPattern, offset: f32) -> Self {
+        let mut schedule = Self {
+            pattern,
+            offset,
+            step: 0,
+            lit: true,
+            phase_left: 0.0,
+            night: 0.0,
+        };
+        schedule.restart();
+        schedule
+    }
+
+    // Back to the start of the pattern for a new night
+    pub fn restart(&mut self) {
+        self.step = 0;
+        self.lit = true;
+        self.phase_left = self.pattern.cycle.first().map_or(0.0, |(lit, _)| *lit);
+        self.night = 0.0;
+
+        // The offset moves through the pattern without counting towards bedtime
+        self.advance(self.offset);
+        self.night = 0.0;
+    }
+
+    // Lights on for a while mid-pattern, e.g. after a prank woke the residents.
+    // Afterwards the pattern carries on with the dark phase it was in.
+    pub fn wake(&mut self, seconds: f32) {
+        if !self.lit && !self.pattern.cycle.is_empty() {
+            self.lit = true;
+            self.phase_left = seconds;
+        }
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.lit
+    }
+
+    // None while the lights are off
+    pub fn time_until_dark(&self) -> Option<f32> {
+        (self.lit && !self.pattern.cycle.is_empty()).then_some(self.phase_left)
+    }
+
+    // True when the lights switched
+    pub fn tick(&mut self, delta: f32) -> bool {
+        let was_lit = self.lit;
+        self.advance(delta);
+        self.lit != was_lit
+    }
+
+    fn advance(&mut self, seconds: f32) {
+        if self.pattern.cycle.is_empty() {
+            return;
+        }
+
+        self.night += seconds;
+        self.phase_left -= seconds;
+        while self.phase_left <= 0.0 && !self.in_bed() {
+            // Zero-length phases would never let the loop finish
+            if self.lit {
+                self.lit = false;
+                self.phase_left += self.pattern.cycle[self.step].1.max(0.1);
+            } else {
+                self.step = (self.step + 1) % self.pattern.cycle.len();
+                self.lit = true;
+                self.phase_left += self.pattern.cycle[self.step].0.max(0.1);
+            }
+        }
+    }
+
+    fn in_bed(&self) -> bool {
+        !self.lit && self.pattern.bedtime.is_some_and(|bedtime| self.night >= bedtime)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(cycle: &[(f32, f32)], bedtime: Option<f32>, offset: f32) -> LightSchedule {
+        let pattern = LightPattern {
+            cycle: cycle.to_vec(),
+            bedtime,
+        };
+        LightSchedule::new(pattern, offset)
+    }
+
+    #[test]
+    fn empty_cycle_stays_lit() {
+        let mut schedule = schedule(&[], None, 0.0);
+        assert!(!schedule.tick(1000.0));
+        assert!(schedule.is_lit());
+        assert_eq!(schedule.time_until_dark(), None);
+    }
+
+    #[test]
+    fn cycle_switches_lights() {
+        let mut schedule = schedule(&[(2.0, 1.0)], None, 0.0);
+        assert_eq!(schedule.time_until_dark(), Some(2.0));
+
+        assert!(schedule.tick(2.0));
+        assert!(!schedule.is_lit());
+        assert!(schedule.tick(1.0));
+        assert!(schedule.is_lit());
+    }
+
+    #[test]
+    fn offset_starts_part_way_through() {
+        let mut schedule = schedule(&[(2.0, 1.0)], None, 2.5);
+        assert!(!schedule.is_lit());
+        assert!(schedule.tick(0.5));
+        assert!(schedule.is_lit());
+    }
+
+    #[test]
+    fn lights_stay_off_after_bedtime() {
+        let mut schedule = schedule(&[(1.0, 1.0)], Some(3.0), 0.0);
+        schedule.tick(10.0);
+        assert!(!schedule.is_lit());
+        assert!(!schedule.tick(100.0));
+        assert!(!schedule.is_lit());
+
+        schedule.restart();
+        assert!(schedule.is_lit());
+    }
+
+    #[test]
+    fn woken_house_goes_dark_again() {
+        let mut schedule = schedule(&[(2.0, 1.0)], None, 0.0);
+        schedule.tick(2.0);
+        schedule.wake(5.0);
+        assert!(schedule.is_lit());
+
+        assert!(!schedule.tick(4.9));
+        assert!(schedule.tick(0.2));
+        assert!(!schedule.is_lit());
+    }
+
+    #[test]
+    fn shipped_level_parses() {
+        let level: Level = ron::from_str(include_str!("../assets/levels/street.level.ron")).unwrap();
+        assert!(!level.houses.is_empty());
+        for house in &level.houses {
+            assert!(level.patterns.contains_key(&house.kind));
+        }
+    }
+}
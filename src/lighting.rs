@@ -0,0 +1,117 @@
+// 2D lighting. A darkness layer covers the world and anything carrying a
+// `LightSource` cuts a pool of light out of it: lit houses, the ghosts, the
+// jack-o'-lantern and bullets. Particles and floating text sit above the layer.
+//
+// The overlay is a single `Material2d` quad using assets/shaders/lighting.wgsl,
+// no render targets or storage buffers, so it works with llvmpipe as well.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+    transform::TransformSystem,
+};
+
+// Has to match the array sizes in the shader
+const MAX_LIGHTS: usize = 32;
+
+// Above houses, ghosts and bullets, below particles and text
+const DARKNESS_Z: f32 = 4.0;
+
+// Bigger than the largest window, with room for screen shake
+const DARKNESS_SIZE: f32 = 4000.0;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<LightingMaterial>::default())
+            .add_systems(Startup, spawn_darkness)
+            // Needs this frame's global transforms
+            .add_systems(PostUpdate, gather_lights.after(TransformSystem::TransformPropagate));
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct LightSource {
+    pub radius: f32,
+    pub color: Color,
+    pub intensity: f32,  // 0.0 off to 1.0 fully lights its radius
+}
+
+impl LightSource {
+    pub fn new(radius: f32, color: Color, intensity: f32) -> Self {
+        Self { radius, color, intensity }
+    }
+}
+
+// Fields sharing binding 0 are packed into the shader's `Lighting` struct in
+// this order
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+struct LightingMaterial {
+    #[uniform(0)]
+    darkness: Vec4,
+    #[uniform(0)]
+    lights: [Vec4; MAX_LIGHTS],  // xy position, z radius, w intensity
+    #[uniform(0)]
+    colors: [Vec4; MAX_LIGHTS],
+    #[uniform(0)]
+    count: u32,
+}
+
+impl Material2d for LightingMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/lighting.wgsl".into()
+    }
+}
+
+fn spawn_darkness(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+) {
+    let darkness = Color::srgba(0.02, 0.02, 0.06, 0.82);
+
+    commands.spawn(MaterialMesh2dBundle {
+        mesh: meshes.add(Rectangle::new(DARKNESS_SIZE, DARKNESS_SIZE)).into(),
+        material: materials.add(LightingMaterial {
+            darkness: Vec4::from_array(darkness.to_linear().to_f32_array()),
+            lights: [Vec4::ZERO; MAX_LIGHTS],
+            colors: [Vec4::ZERO; MAX_LIGHTS],
+            count: 0,
+        }),
+        transform: Transform::from_xyz(0.0, 0.0, DARKNESS_Z),
+        ..default()
+    });
+}
+
+fn gather_lights(
+    darkness_query: Query<&Handle<LightingMaterial>>,
+    mut materials: ResMut<Assets<LightingMaterial>>,
+    light_query: Query<(&GlobalTransform, &LightSource, &InheritedVisibility)>,
+) {
+    let Ok(handle) = darkness_query.get_single() else {
+        return;
+    };
+    let Some(lighting) = materials.get_mut(handle) else {
+        return;
+    };
+
+    // Anything past the limit stays dark; there are far fewer lights than that
+    // in a normal night
+    let mut count = 0;
+    for (transform, light, visibility) in light_query.iter() {
+        if count == MAX_LIGHTS {
+            break;
+        }
+        if light.intensity <= 0.0 || !visibility.get() {
+            continue;
+        }
+
+        let position = transform.translation().truncate();
+        lighting.lights[count] = Vec4::new(position.x, position.y, light.radius, light.intensity);
+        lighting.colors[count] = Vec4::from_array(light.color.to_linear().to_f32_array());
+        count += 1;
+    }
+    lighting.count = count as u32;
+}
@@ -1,13 +1,56 @@
 use bevy::{
     prelude::*,
+    ecs::system::EntityCommands,
     window::PrimaryWindow,
     app::AppExit,
     input::keyboard::KeyCode,
     input::mouse::MouseButton,
 };
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::time::Duration;
 use serde::{Serialize, Deserialize};
 
+mod achievements;
+mod animation;
+mod audio;
+mod candy;
+mod costumes;
+#[cfg(feature = "debug")]
+mod debug_gizmos;
+mod debug_overlay;
+mod difficulty;
+mod events;
+mod indicators;
+mod level;
+mod lighting;
+mod net;
+mod particles;
+mod pranks;
+mod settings;
+mod shop;
+mod stats;
+mod tuning;
+
+use achievements::AchievementsPlugin;
+use animation::{Animator, Clip, Sheet, SpriteAnimationPlugin};
+use audio::GameAudioPlugin;
+use candy::{CandyKind, CandyPlugin, CandySack, LootTable};
+use costumes::{Costume, CostumePlugin};
+use indicators::IndicatorsPlugin;
+use level::{LevelData, LevelPlugin, LightSchedule};
+use lighting::{LightSource, LightingPlugin};
+use debug_overlay::DebugOverlayPlugin;
+use difficulty::{Difficulty, DifficultyPlugin};
+use events::{BalloonPopped, CandyCollected, CandyDeposited, GameEventsPlugin, HouseLightChanged, NightStarted, SackFull, ShotFired};
+use net::{NetId, NetPlugin, NetRole, RemoteInput};
+use particles::{Effect, ParticleEmitter, ParticlePlugin};
+use pranks::PranksPlugin;
+use settings::{Settings, SettingsPlugin};
+use shop::{ShopPlugin, Upgrades};
+use stats::StatsPlugin;
+use tuning::{GameTuning, TuningPlugin};
+
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 enum GameSet {
     FollowMouse,
@@ -19,29 +62,55 @@ enum GameSet {
 
 #[derive(States, Debug, Clone, Eq, PartialEq, Hash, Default)]
 enum GameState {
+    NewGame,  // Picking the difficulty, see `difficulty`
     #[default]
     Playing,
     Paused,
+    Shop,  // Between nights, offline only
+    Wardrobe,
 }
 
+const LIGHTS_OUT_WARNING_SECONDS: f32 = 1.0;
+const GLOW_COLOR: Color = Color::srgb(1.0, 0.8, 0.45);
+
+// Distances from the jack-o'-lantern, the balloon and the centre of the street.
+// Drawn by the `debug` feature's gizmos, see `debug_gizmos`
+const DEPOSIT_RANGE: f32 = 100.0;
+const BULLET_HIT_RANGE: f32 = 50.0;
+const BULLET_DESPAWN_DISTANCE: f32 = 1000.0;
+
 // Update these type definitions
 type BulletQuery<'a> = Query<'a, 'static, (Entity, &'static mut Transform, &'static Bullet)>;
 type BalloonQuery<'a> = Query<'a, 'static, (Entity, &'static Transform), With<BalloonPumpkin>>;
+type VisitorQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Player, &'static mut CandySack, Option<&'static mut PlayerInventory>), With<Ghost>>;
+type RivalQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Ghost, &'static mut Player, &'static mut CandySack, Option<&'static mut PlayerInventory>)>;
 
 fn main() {
-    App::new()
+    let net_role = NetRole::from_args();
+    let settings = Settings::load();
+    let difficulty = Difficulty::from_args();
+    // Networked games have nobody to wait for on a menu
+    let first_state = if difficulty.is_none() && matches!(net_role, NetRole::Offline) {
+        GameState::NewGame
+    } else {
+        GameState::Playing
+    };
+
+    let mut app = App::new();
+    app
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
-                title: "Spooky Pranks!".into(),
-                resolution: (800., 600.).into(),
+                title: format!("Spooky Pranks!{}", net_role.title_suffix()),
+                resolution: settings.window_resolution(),
+                mode: settings.window_mode(),
+                present_mode: settings.present_mode(),
                 ..default()
             }),
             ..default()
         }))
+        .insert_state(first_state)
+        .insert_resource(settings)
         .insert_resource(ClearColor(Color::srgb(0.1, 0.1, 0.15))) // Dark background
-        .insert_resource(TrailSettings {
-            spawn_timer: Timer::from_seconds(0.05, TimerMode::Repeating),
-        })
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -49,39 +118,91 @@ fn main() {
                 spawn_ghost_trail,
                 update_ghost_trail,
                 cursor_position_system.in_set(GameSet::CursorPositionSystem),
-                follow_mouse.in_set(GameSet::FollowMouse),
+                update_player_targets,
+                follow_mouse.in_set(GameSet::FollowMouse).run_if(net::is_authority),
                 float_ghost.in_set(GameSet::FloatGhost),
-                fade_ghost.in_set(GameSet::FadeGhost),
+                fade_ghost.in_set(GameSet::FadeGhost).run_if(net::is_authority),
                 exit_system.in_set(GameSet::ExitSystem),
                 update_house_display,
-                save_game,
-                load_game,
-                switch_house_lights,
+                save_game.run_if(net::is_authority),
+                load_game.run_if(net::is_authority),
+                switch_house_lights.run_if(net::is_authority),
                 update_score_text,
-                update_particles,
-                ghost_house_interaction.run_if(in_state(GameState::Playing)),
-                candy_deposit_system,
+                ghost_house_interaction.run_if(in_state(GameState::Playing)).run_if(net::is_authority),
+                candy_deposit_system.run_if(net::is_authority),
                 animate_progress_particles,
-                bullet_system,
-                shoot_balloon,
+                bullet_system.run_if(net::is_authority),
+                shoot_balloon.run_if(net::is_authority),
             )
-                .run_if(not(in_state(GameState::Paused)))
+                .run_if(in_state(GameState::Playing))
                 .chain(),
         )
         .init_resource::<CursorPosition>()
-        .insert_resource(PlayerInventory {
-            candies: 0,
-            progress_percent: 0.0,
+        .insert_resource(CoopSettings {
+            shared_inventory: !std::env::args().any(|arg| arg == "--separate-inventories"),
+        })
+        .insert_resource(if std::env::args().any(|arg| arg == "--versus") {
+            GameMode::Versus
+        } else {
+            GameMode::Coop
         })
-        .add_systems(Startup, spawn_houses)
+        .insert_resource(difficulty.unwrap_or_default())
+        .init_resource::<VersusRound>()
+        .init_resource::<ScreenShake>()
+        .init_resource::<PlayerInventory>()
+        .init_resource::<BalloonProgress>()
+        .add_event::<ProgressChanged>()
+        .insert_resource(net_role)
+        .add_plugins(GameEventsPlugin)
+        .add_plugins(NetPlugin)
+        .add_plugins(GameAudioPlugin)
+        .add_plugins(TuningPlugin)
+        .add_plugins(LevelPlugin)
+        .add_plugins(DifficultyPlugin)
+        .add_plugins(CandyPlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(ParticlePlugin)
+        .add_plugins(AchievementsPlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ShopPlugin)
+        .add_plugins(CostumePlugin)
+        .add_plugins(SpriteAnimationPlugin)
+        .add_plugins(LightingPlugin)
+        .add_plugins(IndicatorsPlugin)
+        .add_plugins(PranksPlugin)
+        .add_plugins(DebugOverlayPlugin)
+        .add_systems(Startup, spawn_pumpkins)
         .add_systems(
             Update,
             (
-                ghost_house_interaction,
                 animate_floating_text,
-            ),
+                update_house_glow,
+                join_second_player.run_if(net::is_offline),
+                ghost_bump_system.run_if(resource_equals(GameMode::Versus)).run_if(net::is_authority),
+                pick_up_dropped_candy.run_if(net::is_authority),
+                shake_camera,
+                pace_balloon,
+            )
+                .run_if(in_state(GameState::Playing)),
         )
-        .run();
+        .add_systems(
+            Update,
+            (
+                spawn_houses,
+                start_new_night,
+                send_progress_events,
+                declare_versus_winner
+                    .run_if(resource_equals(GameMode::Versus))
+                    .run_if(net::is_authority),
+                update_progress_bars,
+            )
+                .chain(),
+        );
+
+    #[cfg(feature = "debug")]
+    app.add_plugins(debug_gizmos::DebugGizmosPlugin);
+
+    app.run();
 }
 
 #[derive(Resource, Default)]
@@ -103,6 +224,53 @@ struct Ghost {
     state: GhostState,  // Add state to Ghost component
 }
 
+// Which input device steers a ghost
+#[derive(Clone, Copy, PartialEq)]
+enum PlayerControls {
+    Mouse,
+    Keyboard,
+    Gamepad(Gamepad),
+    Remote,  // Steered by a networked client, see `net::RemoteInput`
+}
+
+#[derive(Component)]
+struct Player {
+    id: usize,
+    controls: PlayerControls,
+    target: Vec2,  // Where the ghost is heading this frame
+    aim: Vec2,     // Direction bullets are fired in
+    velocity: Vec2,
+    spook_cooldown: Timer,  // Versus: time before this ghost can bump again
+}
+
+// Co-op options, read when a second ghost joins
+#[derive(Resource)]
+struct CoopSettings {
+    shared_inventory: bool,
+}
+
+const MAX_PLAYERS: usize = 2;
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq)]
+enum GameMode {
+    Coop,    // Ghosts work together towards one progress bar
+    Versus,  // Ghosts race each other to fill their own bars
+}
+
+// Versus: the first ghost to fill its progress bar wins the balloon round
+#[derive(Resource, Default)]
+struct VersusRound {
+    winner: Option<usize>,
+}
+
+// Candy knocked out of a sack, free for any ghost to grab
+#[derive(Component)]
+struct DroppedCandy {
+    kind: CandyKind,
+    velocity: Vec2,
+    pickup_delay: Timer,
+}
+
 #[derive(Component)]
 struct FloatingAnimation {
     original_y: f32,
@@ -135,9 +303,12 @@ enum HouseState {
     Dark,
 }
 
-#[derive(Component)]
+// Who lives there, which decides the house's light pattern in the level data
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
 enum HouseType {
-    First,
+    Family,
+    NightOwl,
+    Party,
 }
 
 #[derive(Component)]
@@ -146,37 +317,122 @@ struct House {
     house_type: HouseType,
     light_status: bool,
     interaction_timer: Timer,
+    rival_timers: HashMap<Entity, Timer>,  // Versus: each ghost's own trick-or-treat progress
+    lights_out: bool,  // The lights are about to go off, see `switch_house_lights`
+    prank_timer: Timer,  // Hold-to-prank progress while dark, see `pranks`
+    pranked: bool,  // Already pranked since the lights went off
+}
+
+impl House {
+    // How far the furthest visitor has got, solo or Versus
+    fn visit_fraction(&self) -> f32 {
+        self.rival_timers
+            .values()
+            .map(Timer::fraction)
+            .fold(self.interaction_timer.fraction(), f32::max)
+    }
+}
+
+// Warm light spilling around a lit house, a child of the house
+#[derive(Component)]
+struct HouseGlow {
+    brightness: f32,  // 0.0 dark to 1.0 fully lit, eases towards the house's state
 }
 
-#[derive(Resource, Serialize, Deserialize, Clone)]
+// The resource is the shared (or player one's) inventory. A ghost that
+// carries its own `PlayerInventory` component banks candy there instead.
+#[derive(Resource, Component, Serialize, Deserialize, Clone, Default)]
 struct PlayerInventory {
-    candies: u32,
-    progress_percent: f32,
+    candies: u32,  // Points to spend in the shop
+    #[serde(default)]
+    collected: BTreeMap<CandyKind, u32>,
+    #[serde(default)]
+    spooks: u32,  // Earned by pranking dark houses, see `pranks`
 }
 
-#[derive(Resource)]
-struct HouseSprites {
-    lit: Handle<Image>,
-    dark: Handle<Image>,
+impl PlayerInventory {
+    // What the candies actually changed by, cursed candy can't take them below 0
+    fn bank(&mut self, kind: CandyKind) -> i32 {
+        let before = self.candies;
+        self.candies = self.candies.saturating_add_signed(kind.points());
+        *self.collected.entry(kind).or_insert(0) += 1;
+        self.candies as i32 - before as i32
+    }
+
+    // Candy spooked out of a sack before it was deposited, `banked` being what
+    // `bank` returned for it
+    fn unbank(&mut self, kind: CandyKind, banked: i32) {
+        self.candies = self.candies.saturating_add_signed(-banked);
+        if let Some(count) = self.collected.get_mut(&kind) {
+            *count = count.saturating_sub(1);
+        }
+    }
 }
 
-#[derive(Component)]
-struct BalloonPumpkin;
+// How full each balloon progress bar is, from 0.0 to 100.0, indexed by
+// `progress_owner`. The `ProgressBar` UI only ever displays this.
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+struct BalloonProgress {
+    percent: [f32; MAX_PLAYERS],
+}
+
+impl BalloonProgress {
+    fn get(&self, owner: usize) -> f32 {
+        self.percent.get(owner).copied().unwrap_or(0.0)
+    }
+
+    fn set(&mut self, owner: usize, percent: f32) {
+        if let Some(current) = self.percent.get_mut(owner) {
+            *current = percent.clamp(0.0, 100.0);
+        }
+    }
+
+    fn add(&mut self, owner: usize, amount: f32) -> f32 {
+        self.set(owner, self.get(owner) + amount);
+        self.get(owner)
+    }
+
+    fn is_full(&self, owner: usize) -> bool {
+        self.get(owner) >= 100.0
+    }
+
+    fn any_full(&self) -> bool {
+        (0..MAX_PLAYERS).any(|owner| self.is_full(owner))
+    }
+}
+
+// Sent whenever a bar's progress changes, whoever changed it
+#[derive(Event)]
+struct ProgressChanged {
+    owner: usize,
+    percent: f32,
+}
+
+// What F5 writes to save_game.json
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    candies: u32,
+    progress_percent: f32,
+    #[serde(default)]  // Saves from before the shop have no upgrades
+    upgrades: Upgrades,
+    #[serde(default)]  // Older saves were all played on Normal
+    difficulty: Difficulty,
+    #[serde(default)]
+    collected: BTreeMap<CandyKind, u32>,
+    #[serde(default)]
+    spooks: u32,
+}
 
 #[derive(Component)]
-struct ScoreText;
+struct BalloonPumpkin;
 
 #[derive(Component)]
-struct Particle {
-    velocity: Vec2,
-    lifetime: Timer,
+struct ScoreText {
+    player: usize,
 }
 
 #[derive(Component)]
-struct CandySack {
-    capacity: u32,
-    current: u32,
-}
+struct ProgressSparkles;
 
 #[derive(Component)]
 struct Pumpkin;  // Just use as a marker component
@@ -188,7 +444,21 @@ struct Bullet {
 }
 
 #[derive(Component)]
-struct ProgressBar;
+struct ProgressBar {
+    player: usize,  // Versus gives every player a bar; co-op shares player 0's
+}
+
+// The whole row a `ProgressBar` sits in, so it can go when its player leaves
+#[derive(Component)]
+struct ProgressBarRow {
+    player: usize,
+}
+
+// Camera shake, decays back to zero on its own
+#[derive(Resource, Default)]
+struct ScreenShake {
+    trauma: f32,  // 0.0 to 1.0
+}
 
 // Add this component to track if we've shown the message
 #[derive(Component)]
@@ -197,77 +467,45 @@ struct FullSackMessage;
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    net_role: Res<NetRole>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
 ) {
     commands.spawn(Camera2dBundle::default());
-
-    // Update paths to match directory structure
-    let house_sprites = HouseSprites {
-        lit: asset_server.load("sprites/houses/house_lit.png"),
-        dark: asset_server.load("sprites/houses/house_dark.png"),
-    };
-    commands.insert_resource(house_sprites);
+    commands.insert_resource(TrailSettings {
+        spawn_timer: Timer::from_seconds(tuning.trail_spawn_seconds, TimerMode::Repeating),
+    });
 
     commands.spawn((
-        SpriteBundle {
-            texture: asset_server.load("sprites/ghost.png"),
-            transform: Transform::from_xyz(0.0, 0.0, 1.0)
-                .with_scale(Vec3::splat(0.2)),
-            sprite: Sprite {
-                color: Color::WHITE,
-                ..default()
-            },
-            ..default()
-        },
-        Ghost { 
-            speed: 10.0,
-            rotation_speed: 5.0,
-            state: GhostState::Normal,
-        },
-        CandySack {
-            capacity: 10,  // Can hold 10 candies before needing to deposit
-            current: 0,
-        },
-        FloatingAnimation { 
-            original_y: 0.0,
-            amplitude: 10.0,
-            frequency: 2.0,
-        },
-        FadeEffect {
-            timer: Timer::from_seconds(3.0, TimerMode::Repeating),
-        },
+        TransformBundle::default(),
+        ParticleEmitter::new(Effect::SparkleShower, 6.0).with_area(Vec2::new(400.0, 300.0)),
+        ProgressSparkles,
     ));
 
-    commands.spawn((
-        TextBundle::from_section(
-            "Candies: 0",
-            TextStyle {
-                font_size: 30.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
-            ..default()
-        }),
-        ScoreText,
-    ));
+    // Networked ghosts only appear once their players connect
+    if matches!(*net_role, NetRole::Offline) {
+        spawn_ghost(&mut commands, &asset_server, &tuning, &upgrades, 0, PlayerControls::Mouse, Vec2::ZERO);
+        spawn_score_text(&mut commands, 0);
+    }
 
-    // Add progress bar UI
+    spawn_progress_bar(&mut commands, 0);
+}
+
+fn spawn_progress_bar(commands: &mut Commands, player_id: usize) {
+    // Add progress bar UI, one row per player
     commands.spawn((
         NodeBundle {
             style: Style {
                 width: Val::Percent(100.0),
                 height: Val::Px(30.0),
                 position_type: PositionType::Absolute,
-                top: Val::Px(10.0),
+                top: Val::Px(10.0 + 30.0 * player_id as f32),
                 justify_content: JustifyContent::Center,
                 ..default()
             },
             ..default()
         },
+        ProgressBarRow { player: player_id },
     )).with_children(|parent| {
         // Background bar
         parent.spawn(NodeBundle {
@@ -291,12 +529,198 @@ fn setup(
                     background_color: Color::srgb(0.8, 0.4, 0.0).into(),
                     ..default()
                 },
-                ProgressBar,
+                ProgressBar { player: player_id },
             ));
         });
     });
 }
 
+fn spawn_ghost<'a>(
+    commands: &'a mut Commands,
+    asset_server: &AssetServer,
+    tuning: &GameTuning,
+    upgrades: &Upgrades,
+    player_id: usize,
+    controls: PlayerControls,
+    position: Vec2,
+) -> EntityCommands<'a> {
+    // Tint the second ghost so the players can tell each other apart
+    let color = if player_id == 0 {
+        Color::WHITE
+    } else {
+        Color::srgb(0.7, 0.85, 1.0)
+    };
+
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(Costume::default().sheet()),
+            transform: Transform::from_xyz(position.x, position.y, 1.0)
+                .with_scale(Vec3::splat(0.2)),
+            sprite: Sprite {
+                color,
+                ..default()
+            },
+            ..default()
+        },
+        Ghost {
+            speed: upgrades.ghost_speed(tuning),
+            rotation_speed: 5.0,
+            state: GhostState::Normal,
+        },
+        Costume::default(),
+        Animator::new(Sheet::Ghost, Clip::Idle),
+        // Just enough to see where you are in the dark
+        LightSource::new(110.0, Color::srgb(0.75, 0.85, 1.0), 0.5),
+        Player {
+            id: player_id,
+            controls,
+            target: position,
+            aim: Vec2::Y,
+            velocity: Vec2::ZERO,
+            spook_cooldown: Timer::from_seconds(1.0, TimerMode::Once),
+        },
+        // Kept in step with the upgrades by `shop::apply_ghost_upgrades`
+        CandySack::new(upgrades.sack_capacity(tuning)),
+        FloatingAnimation {
+            original_y: position.y,
+            amplitude: 10.0,
+            frequency: 2.0,
+        },
+        FadeEffect {
+            timer: Timer::from_seconds(tuning.fade_seconds, TimerMode::Repeating),
+        },
+    ))
+}
+
+fn spawn_score_text(commands: &mut Commands, player_id: usize) {
+    // Player one reads top-left, player two top-right
+    let mut style = Style {
+        position_type: PositionType::Absolute,
+        top: Val::Px(10.0),
+        ..default()
+    };
+    if player_id == 0 {
+        style.left = Val::Px(10.0);
+    } else {
+        style.right = Val::Px(10.0);
+    }
+
+    commands.spawn((
+        TextBundle::from_section(
+            "Candies: 0",
+            TextStyle {
+                font_size: 30.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(style),
+        ScoreText { player: player_id },
+    ));
+}
+
+// Enter joins a keyboard ghost (WASD + Space), Start joins a gamepad ghost
+#[allow(clippy::too_many_arguments)]
+fn join_second_player(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    coop_settings: Res<CoopSettings>,
+    game_mode: Res<GameMode>,
+    players: Query<&Player>,
+) {
+    if players.iter().count() >= MAX_PLAYERS {
+        return;
+    }
+
+    let controls = if keyboard.just_pressed(KeyCode::Enter) {
+        PlayerControls::Keyboard
+    } else if let Some(gamepad) = gamepads.iter().find(|gamepad| {
+        gamepad_buttons.just_pressed(GamepadButton::new(*gamepad, GamepadButtonType::Start))
+    }) {
+        PlayerControls::Gamepad(gamepad)
+    } else {
+        return;
+    };
+
+    let player_id = players.iter().count();
+    spawn_player(&mut commands, &asset_server, &tuning, &upgrades, player_id, controls, &coop_settings, *game_mode);
+}
+
+// Spawns a joining player's ghost along with their share of the HUD
+#[allow(clippy::too_many_arguments)]
+fn spawn_player(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    tuning: &GameTuning,
+    upgrades: &Upgrades,
+    player_id: usize,
+    controls: PlayerControls,
+    coop_settings: &CoopSettings,
+    game_mode: GameMode,
+) -> Entity {
+    let position = if player_id == 0 { Vec2::ZERO } else { Vec2::new(0.0, -150.0) };
+    let mut ghost = spawn_ghost(commands, asset_server, tuning, upgrades, player_id, controls, position);
+    // Rivals never share candy
+    if player_id > 0 && (!coop_settings.shared_inventory || game_mode == GameMode::Versus) {
+        ghost.insert(PlayerInventory::default());
+    }
+    let entity = ghost.id();
+
+    spawn_score_text(commands, player_id);
+    if game_mode == GameMode::Versus && player_id > 0 {
+        spawn_progress_bar(commands, player_id);
+    }
+    entity
+}
+
+fn update_player_targets(
+    cursor_position: Res<CursorPosition>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut player_query: Query<(&mut Player, &Transform, &FloatingAnimation)>,
+) {
+    // How far ahead of the ghost a held direction places its target
+    let reach = 120.0;
+
+    for (mut player, transform, anim) in player_query.iter_mut() {
+        let position = Vec2::new(transform.translation.x, anim.original_y);
+
+        let input = match player.controls {
+            PlayerControls::Remote => continue,
+            PlayerControls::Mouse => {
+                player.target = cursor_position.position;
+                if let Some(aim) = (cursor_position.position - transform.translation.truncate()).try_normalize() {
+                    player.aim = aim;
+                }
+                continue;
+            }
+            PlayerControls::Keyboard => {
+                let mut input = Vec2::ZERO;
+                if keyboard.pressed(KeyCode::KeyW) { input.y += 1.0; }
+                if keyboard.pressed(KeyCode::KeyS) { input.y -= 1.0; }
+                if keyboard.pressed(KeyCode::KeyA) { input.x -= 1.0; }
+                if keyboard.pressed(KeyCode::KeyD) { input.x += 1.0; }
+                input.normalize_or_zero()
+            }
+            PlayerControls::Gamepad(gamepad) => {
+                let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.0);
+                let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.0);
+                Vec2::new(x, y).clamp_length_max(1.0)
+            }
+        };
+
+        player.target = position + input * reach;
+        if let Some(aim) = input.try_normalize() {
+            player.aim = aim;
+        }
+    }
+}
+
 fn cursor_position_system(
     mut cursor_position: ResMut<CursorPosition>,
     q_window: Query<&Window, With<PrimaryWindow>>,
@@ -314,12 +738,12 @@ fn cursor_position_system(
 }
 
 fn follow_mouse(
-    cursor_position: Res<CursorPosition>,
-    mut ghost_query: Query<(&Ghost, &mut Transform, &mut FloatingAnimation)>,
+    mut ghost_query: Query<(&Ghost, &mut Player, &mut Transform, &mut FloatingAnimation, &CandySack)>,
+    tuning: Res<GameTuning>,
     time: Res<Time>,
 ) {
-    if let Ok((ghost, mut ghost_transform, mut anim)) = ghost_query.get_single_mut() {
-        let target = cursor_position.position.extend(ghost_transform.translation.z);
+    for (ghost, mut player, mut ghost_transform, mut anim, candy_sack) in ghost_query.iter_mut() {
+        let target = player.target.extend(ghost_transform.translation.z);
         let current = Vec3::new(
             ghost_transform.translation.x,
             anim.original_y,
@@ -338,42 +762,55 @@ fn follow_mouse(
             ghost_transform.scale = Vec3::splat(scale);
         }
         
-        let movement_t = ease_out_cubic(time.delta_seconds() * ghost.speed);
+        // Heavier sacks are slower to haul around
+        let speed = ghost.speed * (1.0 - tuning.full_sack_slowdown * candy_sack.load());
+        let movement_t = ease_out_cubic(time.delta_seconds() * speed);
         let new_pos = current.lerp(target, movement_t);
         ghost_transform.translation.x = new_pos.x;
         anim.original_y = new_pos.y;
+
+        if time.delta_seconds() > 0.0 {
+            player.velocity = (new_pos - current).truncate() / time.delta_seconds();
+        }
     }
 }
 
 fn float_ghost(
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &FloatingAnimation)>,
+    tuning: Res<GameTuning>,
+    mut query: Query<(&mut Transform, &FloatingAnimation, Option<&CandySack>)>,
 ) {
-    for (mut transform, anim) in query.iter_mut() {
+    for (mut transform, anim, candy_sack) in query.iter_mut() {
+        // A loaded ghost sags and bobs less
+        let load = candy_sack.map_or(0.0, CandySack::load);
+        let amplitude = anim.amplitude * (1.0 - tuning.full_sack_float_damping * load);
+
         // Combine two sine waves for more organic movement
-        let primary_wave = (time.elapsed_seconds() * anim.frequency).sin() * anim.amplitude;
-        let secondary_wave = (time.elapsed_seconds() * (anim.frequency * 2.5)).sin() * (anim.amplitude * 0.3);
+        let primary_wave = (time.elapsed_seconds() * anim.frequency).sin() * amplitude;
+        let secondary_wave = (time.elapsed_seconds() * (anim.frequency * 2.5)).sin() * (amplitude * 0.3);
         transform.translation.y = anim.original_y + primary_wave + secondary_wave;
     }
 }
 
+// The sprite follows along in `animation::animate_ghosts`
 fn fade_ghost(
     time: Res<Time>,
-    asset_server: Res<AssetServer>,
-    mut query: Query<(&mut Handle<Image>, &mut FadeEffect, &mut Ghost)>,
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
+    mut query: Query<(&mut FadeEffect, &mut Ghost)>,
 ) {
-    for (mut texture, mut fade, mut ghost) in query.iter_mut() {
+    for (mut fade, mut ghost) in query.iter_mut() {
         fade.timer.tick(time.delta());
         
         if fade.timer.just_finished() {
             match ghost.state {
                 GhostState::Normal => {
                     ghost.state = GhostState::Faded;
-                    *texture = asset_server.load("sprites/ghost_faded.png");
+                    fade.timer.set_duration(Duration::from_secs_f32(upgrades.faded_seconds(&tuning)));
                 }
                 GhostState::Faded => {
                     ghost.state = GhostState::Normal;
-                    *texture = asset_server.load("sprites/ghost.png");
+                    fade.timer.set_duration(Duration::from_secs_f32(tuning.fade_seconds));
                 }
             }
         }
@@ -392,13 +829,17 @@ fn exit_system(
 fn spawn_ghost_trail(
     mut commands: Commands,
     time: Res<Time>,
+    tuning: Res<GameTuning>,
     mut trail_settings: ResMut<TrailSettings>,
-    ghost_query: Query<(&Transform, &Sprite), With<Ghost>>,
+    ghost_query: Query<(&Transform, &Sprite, &Costume), With<Ghost>>,
 ) {
+    if tuning.is_changed() {
+        trail_settings.spawn_timer.set_duration(Duration::from_secs_f32(tuning.trail_spawn_seconds));
+    }
     trail_settings.spawn_timer.tick(time.delta());
 
     if trail_settings.spawn_timer.just_finished() {
-        if let Ok((ghost_transform, ghost_sprite)) = ghost_query.get_single() {
+        for (ghost_transform, ghost_sprite, costume) in ghost_query.iter() {
             // Randomize trail scale and rotation slightly
             let random_scale = 0.95 + (rand::random::<f32>() * 0.1);
             let random_rotation = ghost_transform.rotation * Quat::from_rotation_z(rand::random::<f32>() * 0.1 - 0.05);
@@ -406,7 +847,7 @@ fn spawn_ghost_trail(
             commands.spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: Color::srgba(1.0, 1.0, 1.0, 0.8),
+                        color: costume.trail_color(ghost_sprite.color).with_alpha(0.8),
                         ..ghost_sprite.clone()
                     },
                     transform: Transform {
@@ -417,7 +858,7 @@ fn spawn_ghost_trail(
                     ..default()
                 },
                 GhostTrail {
-                    lifetime: Timer::from_seconds(0.8, TimerMode::Once),
+                    lifetime: Timer::from_seconds(tuning.trail_lifetime_seconds, TimerMode::Once),
                 },
             ));
         }
@@ -442,57 +883,75 @@ fn update_ghost_trail(
     }
 }
 
+// Put up once the level file has loaded, and rebuilt from scratch when it changes
 fn spawn_houses(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
+    mut level: LevelData,
+    house_query: Query<Entity, With<House>>,
 ) {
-    // Grid configuration
-    let rows = 3;
-    let cols = 3;
-    let spacing = 300.0; // Space between houses
-    
-    // Calculate starting position for top-left house
-    // This centers the grid around (0,0)
-    let start_x = -((cols - 1) as f32 * spacing) / 2.0;
-    let start_y = -((rows - 1) as f32 * spacing) / 2.0;
-
-    // Spawn houses in a grid
-    for row in 0..rows {
-        for col in 0..cols {
-            // Skip the center position (for the pumpkin)
-            if row == 1 && col == 1 {
-                continue;
-            }
+    let Some(level) = level.reloaded() else {
+        return;
+    };
+    for entity in house_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 
-            let x = start_x + (col as f32 * spacing);
-            let y = start_y + (row as f32 * spacing);
-            
-            let light_status = rand::random::<bool>();
-            
-            // Debug print house spawn
-            println!("Spawning house at ({}, {}), light status: {}", x, y, light_status);
-            
-            commands.spawn((
+    // Houses are numbered in level order so clients can match them up
+    for (house_id, house) in level.houses.iter().enumerate() {
+        let (x, y) = house.position;
+        let schedule = level.schedule(house);
+        let light_status = schedule.is_lit();
+
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load(Sheet::House.texture()),
+                transform: Transform::from_xyz(x, y, 0.0)
+                    .with_scale(Vec3::splat(0.5)),
+                ..default()
+            },
+            // Already settled, no flicker on the first frame
+            Animator::new(Sheet::House, if light_status { Clip::LightsOn } else { Clip::LightsOff })
+                .skip_to_end(),
+            House {
+                state: if light_status { HouseState::Lit } else { HouseState::Dark },
+                house_type: house.kind,
+                light_status,
+                interaction_timer: Timer::from_seconds(upgrades.interaction_seconds(&tuning), TimerMode::Once),
+                rival_timers: HashMap::new(),
+                lights_out: false,
+                prank_timer: Timer::from_seconds(upgrades.prank_seconds(&tuning), TimerMode::Once),
+                pranked: false,
+            },
+            schedule,
+            level.loot(house),
+            NetId(house_id as u32),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
                 SpriteBundle {
-                    texture: asset_server.load(if light_status { 
-                        "sprites/houses/house_lit.png" 
-                    } else { 
-                        "sprites/houses/house_dark.png" 
-                    }),
-                    transform: Transform::from_xyz(x, y, 0.0)
-                        .with_scale(Vec3::splat(0.5)),
+                    texture: asset_server.load("sprites/houses/glow.png"),
+                    // Behind the house, spilling well past its edges
+                    transform: Transform::from_xyz(0.0, 0.0, -0.5)
+                        .with_scale(Vec3::splat(3.0)),
+                    sprite: Sprite {
+                        color: GLOW_COLOR.with_alpha(0.0),
+                        ..default()
+                    },
                     ..default()
                 },
-                House {
-                    state: if light_status { HouseState::Lit } else { HouseState::Dark },
-                    house_type: HouseType::First, // Simplified for testing
-                    light_status,  // Make sure this is being set correctly
-                    interaction_timer: Timer::from_seconds(3.0, TimerMode::Once),
+                HouseGlow {
+                    brightness: if light_status { 1.0 } else { 0.0 },
                 },
+                LightSource::new(260.0, GLOW_COLOR, 0.0),
             ));
-        }
+        });
     }
+}
 
+fn spawn_pumpkins(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Spawn pumpkin in the center
     commands.spawn((
         SpriteBundle {
@@ -502,101 +961,344 @@ fn spawn_houses(
             ..default()
         },
         Pumpkin,
+        LightSource::new(150.0, Color::srgb(1.0, 0.5, 0.1), 0.8),
     ));
 
-    // Spawn balloon pumpkin in the center
+    spawn_balloon(&mut commands, &asset_server);
+}
+
+// Spawn balloon pumpkin in the center
+fn spawn_balloon(commands: &mut Commands, asset_server: &AssetServer) {
     commands.spawn((
         SpriteBundle {
-            texture: asset_server.load("sprites/balloon_pumpkin.png"),
+            texture: asset_server.load(Sheet::Balloon.texture()),
             transform: Transform::from_xyz(0.0, 0.0, 0.0)
                 .with_scale(Vec3::splat(0.4)),
             ..default()
         },
+        Animator::new(Sheet::Balloon, Clip::Idle),
         BalloonPumpkin,
         FloatingAnimation {
             original_y: 0.0,
             amplitude: 15.0,    // How far it floats up/down
-            frequency: 1.5,     // How fast it floats
+            frequency: 1.5,     // How fast it floats, see `pace_balloon`
         },
     ));
 }
 
+fn pace_balloon(
+    tuning: Res<GameTuning>,
+    mut balloon_query: Query<(Ref<BalloonPumpkin>, &mut FloatingAnimation)>,
+) {
+    for (balloon, mut floating) in balloon_query.iter_mut() {
+        if tuning.is_changed() || balloon.is_added() {
+            floating.frequency = tuning.balloon_float_speed;
+        }
+    }
+}
+
+// The balloon stops being a target straight away but hangs around for its pop
+// animation
+fn pop_balloon(commands: &mut Commands, balloon: Entity) {
+    commands
+        .entity(balloon)
+        .remove::<(BalloonPumpkin, FloatingAnimation)>()
+        .insert(Animator::new(Sheet::Balloon, Clip::Pop).despawn_when_finished());
+}
+
+// Clear last night's round, put a fresh balloon up and wake the residents
+#[allow(clippy::too_many_arguments)]
+fn start_new_night(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut night_events: EventReader<NightStarted>,
+    mut progress: ResMut<BalloonProgress>,
+    mut versus_round: ResMut<VersusRound>,
+    bullet_query: Query<Entity, With<Bullet>>,
+    balloon_query: Query<(), With<BalloonPumpkin>>,
+    mut house_query: Query<(Entity, &mut House, &mut LightSchedule)>,
+    mut light_events: EventWriter<HouseLightChanged>,
+) {
+    if night_events.read().count() == 0 {
+        return;
+    }
+
+    *progress = BalloonProgress::default();
+    *versus_round = VersusRound::default();
+    for entity in bullet_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if balloon_query.is_empty() {
+        spawn_balloon(&mut commands, &asset_server);
+    }
+
+    for (entity, mut house, mut schedule) in house_query.iter_mut() {
+        schedule.restart();
+        if house.light_status != schedule.is_lit() {
+            set_house_lights(&mut house, schedule.is_lit());
+            light_events.send(HouseLightChanged {
+                house: entity,
+                lit: house.light_status,
+            });
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ghost_house_interaction(
     mut commands: Commands,
-    mut ghost_query: Query<(&Transform, &mut Ghost, &mut CandySack)>,
-    mut houses_query: Query<(&Transform, &mut House, &mut Sprite)>,
+    mut collected_events: EventWriter<CandyCollected>,
+    mut sack_full_events: EventWriter<SackFull>,
+    mut ghost_query: VisitorQuery,
+    mut houses_query: Query<(Entity, &Transform, &mut House, &mut Sprite, &LootTable)>,
     mut inventory: ResMut<PlayerInventory>,
     message_query: Query<Entity, With<FullSackMessage>>, // Query to check if message exists
+    game_mode: Res<GameMode>,
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
     time: Res<Time>,
 ) {
-    let ghost_range = 100.0;
+    let ghost_range = upgrades.interaction_range(&tuning);
 
-    if let Ok((ghost_transform, _, mut candy_sack)) = ghost_query.get_single_mut() {
-        // Only show the message once when the sack becomes full and no message exists
-        if candy_sack.current == candy_sack.capacity && message_query.is_empty() {
-            commands.spawn((
-                Text2dBundle {
-                    text: Text::from_section(
-                        "Move to center pumpkin to deposit!",
-                        TextStyle {
-                            font_size: 20.0,
-                            color: Color::WHITE,
-                            ..default()
-                        },
-                    ),
-                    transform: Transform::from_xyz(0.0, 100.0, 10.0),
-                    ..default()
-                },
-                FloatingText {
-                    timer: Timer::from_seconds(1.0, TimerMode::Once),
-                    initial_position: Vec3::new(0.0, 100.0, 10.0),
-                },
-                FullSackMessage,
-            ));
-        } else if candy_sack.current < candy_sack.capacity {
-            // Remove the message if it exists and sack is no longer full
-            for message_entity in message_query.iter() {
-                commands.entity(message_entity).despawn_recursive();
-            }
+    // Only show the message once when a sack becomes full and no message exists
+    let any_sack_full = ghost_query
+        .iter()
+        .any(|(_, _, _, candy_sack, _)| candy_sack.is_full());
+    if any_sack_full && message_query.is_empty() {
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "Move to center pumpkin to deposit!",
+                    TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_xyz(0.0, 100.0, 10.0),
+                ..default()
+            },
+            FloatingText {
+                timer: Timer::from_seconds(1.0, TimerMode::Once),
+                initial_position: Vec3::new(0.0, 100.0, 10.0),
+            },
+            FullSackMessage,
+        ));
+    } else if !any_sack_full {
+        // Remove the message if it exists and no sack is full anymore
+        for message_entity in message_query.iter() {
+            commands.entity(message_entity).despawn_recursive();
         }
+    }
 
-        for (house_transform, mut house, mut sprite) in houses_query.iter_mut() {
-            if !house.light_status {
-                continue;
-            }
+    for (house_entity, house_transform, mut house, mut sprite, loot) in houses_query.iter_mut() {
+        if !house.light_status {
+            continue;
+        }
 
-            let distance = ghost_transform.translation.distance(house_transform.translation);
-            
-            if distance < ghost_range {
-                // Visual feedback - house turns slightly green when in range
-                sprite.color = Color::srgb(0.8, 1.0, 0.8);
-                
+        // Ghosts in range of this house with room in their sack, closest first
+        let mut visitors: Vec<(Entity, f32)> = ghost_query
+            .iter()
+            .filter(|(_, _, _, candy_sack, _)| !candy_sack.is_full())
+            .map(|(entity, ghost_transform, _, _, _)| {
+                (entity, ghost_transform.translation.distance(house_transform.translation))
+            })
+            .filter(|(_, distance)| *distance < ghost_range)
+            .collect();
+        visitors.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        if visitors.is_empty() {
+            // Reset color when out of range
+            sprite.color = Color::WHITE;
+            house.interaction_timer.reset();
+            house.rival_timers.clear();
+            continue;
+        }
+
+        // Visual feedback - house turns slightly green (or blue) when in range
+        sprite.color = settings.palette().house_in_range();
+
+        let finisher = match *game_mode {
+            // One shared timer, the closest ghost gets the candy
+            GameMode::Coop => {
                 house.interaction_timer.tick(time.delta());
-                
-                // Debug print when timer is running
-                if house.interaction_timer.fraction() > 0.0 {
-                    println!("Trick or treating at house: {}%", house.interaction_timer.fraction() * 100.0);
+                house.interaction_timer.just_finished().then_some(visitors[0].0)
+            }
+            // Every ghost runs its own timer, the first one done gets the candy
+            GameMode::Versus => {
+                let duration = house.interaction_timer.duration();
+                house.rival_timers.retain(|ghost, _| visitors.iter().any(|(visitor, _)| visitor == ghost));
+
+                let mut finisher = None;
+                for (visitor, _) in &visitors {
+                    let timer = house
+                        .rival_timers
+                        .entry(*visitor)
+                        .or_insert_with(|| Timer::new(duration, TimerMode::Once));
+                    timer.tick(time.delta());
+                    if timer.just_finished() && finisher.is_none() {
+                        finisher = Some(*visitor);
+                    }
                 }
+                finisher
+            }
+        };
+
+        if let Some(finisher) = finisher {
+            if let Ok((_, _, player, mut candy_sack, own_inventory)) = ghost_query.get_mut(finisher) {
+                // Nothing comes out if none of the house's candy fits
+                if let Some(kind) = loot.draw(candy_sack.free()) {
+                    let banked = match own_inventory {
+                        Some(mut own_inventory) => own_inventory.bank(kind),
+                        None => inventory.bank(kind),
+                    };
+                    candy_sack.add(kind, banked);
 
-                if house.interaction_timer.just_finished() {
-                    println!("Timer finished! Adding candy!");
-                    candy_sack.current += 1;
-                    inventory.candies += 1;
-                    
                     // Spawn very visible text
                     spawn_floating_text(
                         &mut commands,
                         house_transform.translation,
-                        &format!("Total Candies: {}", inventory.candies)
+                        &format!("{} ({:+})", kind.name(), kind.points())
                     );
-                    
-                    // Reset timer
-                    house.interaction_timer.reset();
+                    collected_events.send(CandyCollected {
+                        player: player.id,
+                        house: Some(house_entity),
+                        kind,
+                        position: house_transform.translation,
+                    });
+                    if candy_sack.is_full() {
+                        sack_full_events.send(SackFull { player: player.id });
+                    }
+                }
+            }
+
+            // Reset timers, rivals have to start over too
+            house.interaction_timer.reset();
+            house.rival_timers.clear();
+        }
+    }
+}
+
+// Versus: a ghost that rams into a rival spooks candy out of the rival's sack
+fn ghost_bump_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut ghost_query: RivalQuery,
+) {
+    let bump_range = 60.0;
+    let min_bump_speed = 150.0;  // Ghosts just hovering next to each other don't count
+    let candies_dropped = (3.0 * tuning.aggression).round() as u32;
+
+    for (_, _, _, mut player, _, _) in ghost_query.iter_mut() {
+        player.spook_cooldown.tick(time.delta());
+    }
+
+    // Faded ghosts drift straight through each other
+    let ghosts: Vec<(Entity, Vec3, f32)> = ghost_query
+        .iter()
+        .filter(|(_, _, ghost, player, _, _)| {
+            player.spook_cooldown.finished() && matches!(ghost.state, GhostState::Normal)
+        })
+        .map(|(entity, transform, _, player, _, _)| (entity, transform.translation, player.velocity.length()))
+        .collect();
+
+    for (i, &(a, a_pos, a_speed)) in ghosts.iter().enumerate() {
+        for &(b, b_pos, b_speed) in &ghosts[i + 1..] {
+            if a_pos.distance(b_pos) > bump_range || a_speed.max(b_speed) < min_bump_speed {
+                continue;
+            }
+
+            // The faster ghost does the spooking
+            let victim = if a_speed >= b_speed { b } else { a };
+
+            if let Ok((_, transform, _, _, mut candy_sack, mut own_inventory)) = ghost_query.get_mut(victim) {
+                let dropped = candy_sack.spill(candies_dropped);
+
+                for (kind, banked) in dropped {
+                    match own_inventory.as_mut() {
+                        Some(own_inventory) => own_inventory.unbank(kind, banked),
+                        None => inventory.unbank(kind, banked),
+                    }
+
+                    let angle = rand::random::<f32>() * std::f32::consts::TAU;
+                    let speed = rand::random::<f32>() * 100.0 + 150.0;
+
+                    commands.spawn((
+                        SpriteBundle {
+                            texture: asset_server.load("sprites/sparkle.png"),
+                            transform: Transform::from_xyz(transform.translation.x, transform.translation.y, 0.5)
+                                .with_scale(Vec3::splat(0.08)),
+                            sprite: Sprite {
+                                color: kind.color(),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        DroppedCandy {
+                            kind,
+                            velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                            pickup_delay: Timer::from_seconds(0.5, TimerMode::Once),
+                        },
+                    ));
+                }
+
+                spawn_floating_text(&mut commands, transform.translation, "Spooked!");
+            }
+
+            // Both ghosts need a moment to recover
+            for ghost in [a, b] {
+                if let Ok((_, _, _, mut player, _, _)) = ghost_query.get_mut(ghost) {
+                    player.spook_cooldown.reset();
+                }
+            }
+        }
+    }
+}
+
+fn pick_up_dropped_candy(
+    mut commands: Commands,
+    mut collected_events: EventWriter<CandyCollected>,
+    mut sack_full_events: EventWriter<SackFull>,
+    time: Res<Time>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut candy_query: Query<(Entity, &mut Transform, &mut DroppedCandy), Without<Ghost>>,
+    mut ghost_query: Query<(&Transform, &Player, &mut CandySack, Option<&mut PlayerInventory>), With<Ghost>>,
+) {
+    let pickup_range = 40.0;
+
+    for (entity, mut transform, mut candy) in candy_query.iter_mut() {
+        // Scatter outwards and slow to a stop
+        transform.translation += (candy.velocity * time.delta_seconds()).extend(0.0);
+        candy.velocity *= 1.0 - (4.0 * time.delta_seconds()).min(1.0);
+
+        candy.pickup_delay.tick(time.delta());
+        if !candy.pickup_delay.finished() {
+            continue;
+        }
+
+        for (ghost_transform, player, mut candy_sack, own_inventory) in ghost_query.iter_mut() {
+            let distance = ghost_transform.translation.truncate().distance(transform.translation.truncate());
+            if distance < pickup_range && candy_sack.fits(candy.kind) {
+                let banked = match own_inventory {
+                    Some(mut own_inventory) => own_inventory.bank(candy.kind),
+                    None => inventory.bank(candy.kind),
+                };
+                candy_sack.add(candy.kind, banked);
+                collected_events.send(CandyCollected {
+                    player: player.id,
+                    house: None,
+                    kind: candy.kind,
+                    position: transform.translation,
+                });
+                if candy_sack.is_full() {
+                    sack_full_events.send(SackFull { player: player.id });
                 }
-            } else {
-                // Reset color when out of range
-                sprite.color = Color::WHITE;
-                house.interaction_timer.reset();
+                commands.entity(entity).despawn();
+                break;
             }
         }
     }
@@ -656,165 +1358,276 @@ fn animate_floating_text(
     }
 }
 
-fn update_house_display(
-    mut house_query: Query<(&House, &mut Handle<Image>)>,
-    house_sprites: Res<HouseSprites>,
+// Lights flicker on and off rather than snapping
+fn update_house_display(mut house_query: Query<(&House, &mut Animator)>) {
+    for (house, mut animator) in house_query.iter_mut() {
+        let clip = match (house.state, &house.house_type) {
+            (HouseState::Lit, _) if house.lights_out => Clip::Dimming,
+            (HouseState::Lit, _) => Clip::LightsOn,
+            (HouseState::Dark, _) => Clip::LightsOff,
+        };
+        animator.play(clip);
+    }
+}
+
+fn update_house_glow(
+    time: Res<Time>,
+    house_query: Query<(&House, &Children)>,
+    mut glow_query: Query<(&mut HouseGlow, &mut Sprite, &mut LightSource)>,
 ) {
-    for (house, mut sprite) in house_query.iter_mut() {
-        let new_sprite = match (house.state, &house.house_type) {
-            (HouseState::Lit, _) => house_sprites.lit.clone(),
-            (HouseState::Dark, _) => house_sprites.dark.clone(),
+    let fade_speed = 3.0;  // Full brightness change in a third of a second
+    let max_alpha = 0.35;
+
+    for (house, children) in house_query.iter() {
+        let target = match house.state {
+            HouseState::Lit => 1.0,
+            HouseState::Dark => 0.0,
         };
-        *sprite = new_sprite;
+
+        for child in children.iter() {
+            let Ok((mut glow, mut sprite, mut light)) = glow_query.get_mut(*child) else {
+                continue;
+            };
+            let step = fade_speed * time.delta_seconds();
+            glow.brightness += (target - glow.brightness).clamp(-step, step);
+
+            // Sputters along with the porch light while it's about to go out
+            let flicker = if house.lights_out { 0.4 + 0.6 * rand::random::<f32>() } else { 1.0 };
+            sprite.color = GLOW_COLOR.with_alpha(max_alpha * glow.brightness * flicker);
+            // A dark house fades into the shadows along with its glow
+            light.intensity = glow.brightness * flicker;
+        }
     }
 }
 
 fn save_game(
     keyboard: Res<ButtonInput<KeyCode>>,
     inventory: Res<PlayerInventory>,
-    progress_bar_query: Query<&Style, With<ProgressBar>>,
+    progress: Res<BalloonProgress>,
+    upgrades: Res<Upgrades>,
+    difficulty: Res<Difficulty>,
 ) {
     if keyboard.just_pressed(KeyCode::F5) {
-        // Create a copy of inventory with current progress
-        let mut save_inventory = (*inventory).clone();
-        
-        // Update progress from progress bar
-        if let Ok(style) = progress_bar_query.get_single() {
-            if let Val::Percent(progress) = style.width {
-                save_inventory.progress_percent = progress;
-            }
+        // Saves keep player one's progress
+        let save = SaveGame {
+            candies: inventory.candies,
+            progress_percent: progress.get(0),
+            upgrades: upgrades.clone(),
+            difficulty: *difficulty,
+            collected: inventory.collected.clone(),
+            spooks: inventory.spooks,
+        };
+
+        match serde_json::to_string(&save) {
+            Ok(save_data) => match fs::write("save_game.json", save_data) {
+                Ok(()) => info!(target: "halloweengame::save", "Game saved! Progress: {}%", save.progress_percent),
+                Err(error) => error!(target: "halloweengame::save", "Failed to save the game: {}", error),
+            },
+            Err(error) => error!(target: "halloweengame::save", "Failed to save the game: {}", error),
         }
-        
-        let save_data = serde_json::to_string(&save_inventory).unwrap();
-        fs::write("save_game.json", save_data).unwrap();
-        println!("Game saved! Progress: {}%", save_inventory.progress_percent);
     }
 }
 
 fn load_game(
     keyboard: Res<ButtonInput<KeyCode>>,
     mut inventory: ResMut<PlayerInventory>,
-    mut progress_bar_query: Query<(&mut Style, &mut BackgroundColor), With<ProgressBar>>,
+    mut progress: ResMut<BalloonProgress>,
+    mut upgrades: ResMut<Upgrades>,
+    mut difficulty: ResMut<Difficulty>,
 ) {
     if keyboard.just_pressed(KeyCode::F9) {
         if let Ok(save_data) = fs::read_to_string("save_game.json") {
-            if let Ok(loaded_inventory) = serde_json::from_str::<PlayerInventory>(&save_data) {
-                // Update progress bar
-                if let Ok((mut style, mut background_color)) = progress_bar_query.get_single_mut() {
-                    style.width = Val::Percent(loaded_inventory.progress_percent);
-                    
-                    // Update color if progress is 100%
-                    if loaded_inventory.progress_percent >= 100.0 {
-                        *background_color = Color::srgb(1.0, 0.5, 0.0).into();
-                    }
-                }
-                
-                *inventory = loaded_inventory;
-                println!("Game loaded! Progress: {}%", inventory.progress_percent);
+            if let Ok(save) = serde_json::from_str::<SaveGame>(&save_data) {
+                inventory.candies = save.candies;
+                inventory.collected = save.collected;
+                inventory.spooks = save.spooks;
+                progress.set(0, save.progress_percent);
+                *upgrades = save.upgrades;
+                // The run carries on at the difficulty it was started with
+                difficulty.set_if_neq(save.difficulty);
+                info!(target: "halloweengame::save", "Game loaded! Progress: {}%", save.progress_percent);
             }
         }
     }
 }
 
-// Add a new system for light switching
+// Each house follows its own schedule from the level data
 fn switch_house_lights(
     time: Res<Time>,
-    mut houses: Query<(&mut House, &mut Handle<Image>)>,
-    house_sprites: Res<HouseSprites>,
+    tuning: Res<GameTuning>,
+    mut houses: Query<(Entity, &mut House, &mut LightSchedule)>,
+    mut light_events: EventWriter<HouseLightChanged>,
 ) {
-    // Switch lights every few seconds
-    let switch_interval = 5.0; // Adjust this value to control frequency
-    let time_since_startup = time.elapsed_seconds();
-    
-    if time_since_startup % switch_interval < time.delta_seconds() {
-        // Randomly select houses to switch
-        for (mut house, mut sprite) in houses.iter_mut() {
-            if rand::random::<f32>() < 0.3 { // 30% chance to switch each house
-                house.light_status = !house.light_status;
-                house.state = if house.light_status { 
-                    HouseState::Lit 
-                } else { 
-                    HouseState::Dark 
-                };
-                
-                *sprite = if house.light_status {
-                    house_sprites.lit.clone()
-                } else {
-                    house_sprites.dark.clone()
-                };
-            }
+    for (entity, mut house, mut schedule) in houses.iter_mut() {
+        if schedule.tick(time.delta_seconds() * tuning.light_pace) {
+            set_house_lights(&mut house, schedule.is_lit());
+            light_events.send(HouseLightChanged {
+                house: entity,
+                lit: house.light_status,
+            });
+        }
+
+        // The porch light flickers for a while first so nobody gets caught mid
+        // trick-or-treat without warning
+        let lights_out = schedule
+            .time_until_dark()
+            .is_some_and(|seconds| seconds / tuning.light_pace <= LIGHTS_OUT_WARNING_SECONDS);
+        if house.lights_out != lights_out {
+            house.lights_out = lights_out;
         }
     }
 }
 
-fn update_score_text(
-    inventory: Res<PlayerInventory>,
-    mut query: Query<&mut Text, With<ScoreText>>,
+fn set_house_lights(house: &mut House, lit: bool) {
+    house.light_status = lit;
+    house.state = if lit { HouseState::Lit } else { HouseState::Dark };
+    if lit {
+        // The next time the lights go off it's a fresh house to prank
+        house.prank_timer.reset();
+        house.pranked = false;
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
 ) {
-    if let Ok(mut text) = query.get_single_mut() {
-        text.sections[0].value = format!("Candies: {}", inventory.candies);
+    let max_offset = 12.0;
+    screen_shake.trauma = (screen_shake.trauma - 1.5 * time.delta_seconds()).max(0.0);
+
+    // Squaring the trauma makes small shakes subtle and big ones punchy
+    let strength = if settings.screen_shake { screen_shake.trauma.powi(2) } else { 0.0 };
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = (rand::random::<f32>() * 2.0 - 1.0) * max_offset * strength;
+        transform.translation.y = (rand::random::<f32>() * 2.0 - 1.0) * max_offset * strength;
     }
 }
 
-fn update_particles(
+fn send_progress_events(
+    progress: Res<BalloonProgress>,
+    mut last_sent: Local<[f32; MAX_PLAYERS]>,
+    mut progress_events: EventWriter<ProgressChanged>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for (owner, percent) in progress.percent.iter().enumerate() {
+        if *percent != last_sent[owner] {
+            last_sent[owner] = *percent;
+            progress_events.send(ProgressChanged { owner, percent: *percent });
+        }
+    }
+}
+
+// Versus: the first ghost whose bar fills wins the balloon round
+fn declare_versus_winner(
     mut commands: Commands,
-    time: Res<Time>,
-    mut particles: Query<(Entity, &mut Transform, &mut Particle)>,
+    mut progress_events: EventReader<ProgressChanged>,
+    mut versus_round: ResMut<VersusRound>,
 ) {
-    for (entity, mut transform, mut particle) in particles.iter_mut() {
-        particle.lifetime.tick(time.delta());
-        if particle.lifetime.finished() {
-            commands.entity(entity).despawn();
-        } else {
-            transform.translation.x += particle.velocity.x * time.delta_seconds();
-            transform.translation.y += particle.velocity.y * time.delta_seconds();
+    for event in progress_events.read() {
+        if event.percent >= 100.0 && versus_round.winner.is_none() {
+            versus_round.winner = Some(event.owner);
+            spawn_floating_text(
+                &mut commands,
+                Vec3::new(0.0, 150.0, 10.0),
+                &format!("Player {} wins the balloon round!", event.owner + 1)
+            );
+        }
+    }
+}
+
+// Draws each bar from `BalloonProgress` in the chosen palette
+fn update_progress_bars(
+    progress: Res<BalloonProgress>,
+    settings: Res<Settings>,
+    mut progress_bar_query: Query<(Ref<ProgressBar>, &mut Style, &mut BackgroundColor)>,
+) {
+    let palette = settings.palette();
+
+    for (bar, mut style, mut background_color) in progress_bar_query.iter_mut() {
+        if !progress.is_changed() && !settings.is_changed() && !bar.is_added() {
+            continue;
         }
+
+        style.width = Val::Percent(progress.get(bar.player));
+        *background_color = if progress.is_full(bar.player) {
+            palette.progress_full().into()
+        } else {
+            palette.progress_fill().into()
+        };
+    }
+}
+
+fn update_score_text(
+    inventory: Res<PlayerInventory>,
+    ghost_query: Query<(&Player, &CandySack, Option<&PlayerInventory>)>,
+    mut query: Query<(&mut Text, &ScoreText)>,
+) {
+    let coop = ghost_query.iter().count() > 1;
+
+    for (mut text, score_text) in query.iter_mut() {
+        let Some((player, candy_sack, own_inventory)) = ghost_query
+            .iter()
+            .find(|(player, _, _)| player.id == score_text.player)
+        else {
+            continue;
+        };
+        let candies = own_inventory.unwrap_or(&inventory).candies;
+
+        text.sections[0].value = if coop {
+            format!("P{} Candies: {} ({}/{})", player.id + 1, candies, candy_sack.used(), candy_sack.capacity)
+        } else {
+            format!("Candies: {}", candies)
+        };
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn candy_deposit_system(
     mut commands: Commands,
-    mut ghost_query: Query<(&Transform, &mut CandySack)>,
+    mut deposited_events: EventWriter<CandyDeposited>,
+    mut ghost_query: Query<(&Transform, &Player, &mut CandySack)>,
     pumpkin_query: Query<&Transform, With<Pumpkin>>,
-    mut progress_bar_query: Query<(&mut Style, &mut BackgroundColor), With<ProgressBar>>,
+    mut progress: ResMut<BalloonProgress>,
     message_query: Query<Entity, With<FullSackMessage>>,
+    game_mode: Res<GameMode>,
+    tuning: Res<GameTuning>,
 ) {
-    let deposit_range = 100.0;
+    let Ok(pumpkin_transform) = pumpkin_query.get_single() else {
+        return;
+    };
 
-    if let (Ok((ghost_transform, mut candy_sack)), Ok(pumpkin_transform)) = 
-        (ghost_query.get_single_mut(), pumpkin_query.get_single()) {
-        
+    for (ghost_transform, player, mut candy_sack) in ghost_query.iter_mut() {
         let distance = ghost_transform.translation.distance(pumpkin_transform.translation);
         
-        if distance < deposit_range && candy_sack.current > 0 {
-            // Update progress bar (25% per full sack)
-            if let Ok((mut style, mut background_color)) = progress_bar_query.get_single_mut() {
-                let current_width = if let Val::Percent(width) = style.width {
-                    width
-                } else {
-                    0.0
-                };
-                
-                // Calculate progress increase (25% per full sack)
-                let progress_increase = (candy_sack.current as f32 / candy_sack.capacity as f32) * 25.0;
-                let new_width = (current_width + progress_increase).min(100.0);
-                style.width = Val::Percent(new_width);
-                
-                // Change color when full
-                if new_width >= 100.0 {
-                    *background_color = Color::srgb(1.0, 0.5, 0.0).into();
-                }
-            }
+        if distance < DEPOSIT_RANGE && !candy_sack.is_empty() {
+            let owner = progress_owner(player, *game_mode);
+
+            // The bar goes by how full the sack is, candy points only go to the shop
+            let progress_increase = candy_sack.load() * tuning.progress_per_full_sack;
+            progress.add(owner, progress_increase);
             
             // Spawn deposit effect
             spawn_floating_text(
                 &mut commands,
                 pumpkin_transform.translation,
-                &format!("Deposited {} candies!", candy_sack.current)
+                &format!("Deposited {} candies!", candy_sack.len())
             );
+            deposited_events.send(CandyDeposited {
+                player: player.id,
+                amount: candy_sack.len(),
+                slots: candy_sack.used(),
+                capacity: candy_sack.capacity,
+                position: pumpkin_transform.translation,
+            });
             
             // Reset candy sack
-            candy_sack.current = 0;
+            candy_sack.empty();
             
             // Remove full sack message if it exists
             for message_entity in message_query.iter() {
@@ -824,60 +1637,23 @@ fn candy_deposit_system(
     }
 }
 
-#[derive(Default)]
-struct BurstConfig {
-    count: i32,
-    min_speed: f32,
-    max_speed: f32,
-    min_scale: f32,
-    lifetime: f32,
-    color: Color,
-}
-
-fn spawn_money_burst(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    position: Vec3,
-    config: BurstConfig,
-) {
-    for i in 0..config.count {
-        let angle = (i as f32 / config.count as f32) * std::f32::consts::TAU;
-        let speed = rand::random::<f32>() * (config.max_speed - config.min_speed) + config.min_speed;
-        let velocity = Vec2::new(angle.cos(), angle.sin()) * speed;
-        
-        let spread = rand::random::<f32>() * 0.2 - 0.1;
-        let particle_angle = angle + spread;
-        
-        let scale_variation = rand::random::<f32>() * 0.1;
-        let scale = config.min_scale + scale_variation;
-        
-        commands.spawn((
-            SpriteBundle {
-                texture: asset_server.load("sprites/money_shot.png"),
-                transform: Transform::from_xyz(position.x, position.y, 2.0)
-                    .with_scale(Vec3::splat(scale))
-                    .with_rotation(Quat::from_rotation_z(particle_angle)),
-                sprite: Sprite {
-                    color: config.color,
-                    ..default()
-                },
-                ..default()
-            },
-            Particle {
-                velocity,
-                lifetime: Timer::from_seconds(config.lifetime, TimerMode::Once),
-            },
-        ));
+// The progress bar a player's deposits count towards
+fn progress_owner(player: &Player, game_mode: GameMode) -> usize {
+    match game_mode {
+        GameMode::Coop => 0,
+        GameMode::Versus => player.id,
     }
 }
 
 fn bullet_system(
     mut commands: Commands,
+    mut popped_events: EventWriter<BalloonPopped>,
     mut bullets_and_balloons: ParamSet<(BulletQuery, BalloonQuery)>,
     time: Res<Time>,
-    asset_server: Res<AssetServer>,
+    mut screen_shake: ResMut<ScreenShake>,
 ) {
-    let balloon_pos = bullets_and_balloons.p1()
+    // Cleared after the first hit, so a volley only pops the balloon once
+    let mut balloon = bullets_and_balloons.p1()
         .get_single()
         .ok()
         .map(|(entity, transform)| (entity, transform.translation));
@@ -888,151 +1664,96 @@ fn bullet_system(
         transform.translation.y += bullet.direction.y * bullet.speed * time.delta_seconds();
 
         // Check collision with balloon
-        if let Some((balloon_entity, balloon_pos)) = balloon_pos {
+        if let Some((balloon_entity, balloon_pos)) = balloon {
             let distance = transform.translation.distance(balloon_pos);
-            if distance < 50.0 {
-                // Inner burst
-                spawn_money_burst(&mut commands, &asset_server, balloon_pos, BurstConfig {
-                    count: 12,
-                    min_speed: 200.0,
-                    max_speed: 300.0,
-                    min_scale: 0.1,
-                    lifetime: 0.5,
-                    color: Color::srgb(1.0, 0.9, 0.3),
-                });
-                
-                // Middle burst
-                spawn_money_burst(&mut commands, &asset_server, balloon_pos, BurstConfig {
-                    count: 8,
-                    min_speed: 150.0,
-                    max_speed: 250.0,
-                    min_scale: 0.15,
-                    lifetime: 0.7,
-                    color: Color::srgb(1.0, 0.8, 0.0),
-                });
-                
-                // Outer burst
-                spawn_money_burst(&mut commands, &asset_server, balloon_pos, BurstConfig {
-                    count: 6,
-                    min_speed: 100.0,
-                    max_speed: 200.0,
-                    min_scale: 0.2,
-                    lifetime: 1.0,
-                    color: Color::srgb(0.9, 0.7, 0.0),
-                });
-
-                // Trailing particles
-                for _ in 0..4 {
-                    let angle = rand::random::<f32>() * std::f32::consts::TAU;
-                    let speed = rand::random::<f32>() * 50.0 + 25.0;
-                    let velocity = Vec2::new(angle.cos(), angle.sin()) * speed;
-                    
-                    commands.spawn((
-                        SpriteBundle {
-                            texture: asset_server.load("sprites/money_shot.png"),
-                            transform: Transform::from_xyz(balloon_pos.x, balloon_pos.y, 2.0)
-                                .with_scale(Vec3::splat(0.25))
-                                .with_rotation(Quat::from_rotation_z(angle)),
-                            sprite: Sprite {
-                                color: Color::srgb(1.0, 0.6, 0.0),
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        Particle {
-                            velocity,
-                            lifetime: Timer::from_seconds(1.5, TimerMode::Once),
-                        },
-                    ));
-                }
-
+            if distance < BULLET_HIT_RANGE {
                 // Spawn hit text with sparkle emoji
                 spawn_floating_text(
                     &mut commands,
                     balloon_pos,
                     "JACKPOT! 💰✨"
                 );
+                popped_events.send(BalloonPopped { position: balloon_pos });
+                screen_shake.trauma = (screen_shake.trauma + 0.8).min(1.0);
 
                 commands.entity(bullet_entity).despawn();
-                commands.entity(balloon_entity).despawn();
+                pop_balloon(&mut commands, balloon_entity);
+                balloon = None;
+                continue;
             }
         }
 
         // Despawn bullets that go off screen
-        if transform.translation.length() > 1000.0 {
+        if transform.translation.length() > BULLET_DESPAWN_DISTANCE {
             commands.entity(bullet_entity).despawn();
         }
     }
 }
 
+// Sparkles drift across the screen while any bar is full
 fn animate_progress_particles(
-    mut commands: Commands,
-    _time: Res<Time>,
-    progress_bar: Query<&Style, With<ProgressBar>>,
-    asset_server: Res<AssetServer>,
+    progress: Res<BalloonProgress>,
+    mut emitter_query: Query<&mut ParticleEmitter, With<ProgressSparkles>>,
 ) {
-    if let Ok(style) = progress_bar.get_single() {
-        if let Val::Percent(progress) = style.width {
-            if progress >= 100.0 && rand::random::<f32>() < 0.1 {
-                let x = rand::random::<f32>() * 800.0 - 400.0;
-                let y = rand::random::<f32>() * 600.0 - 300.0;
-                
-                commands.spawn((
-                    SpriteBundle {
-                        texture: asset_server.load("sprites/sparkle.png"),
-                        transform: Transform::from_xyz(x, y, 5.0)
-                            .with_scale(Vec3::splat(0.2)),
-                        sprite: Sprite {
-                            color: Color::srgb(1.0, 0.9, 0.3),
-                            ..default()
-                        },
-                        ..default()
-                    },
-                    Particle {
-                        velocity: Vec2::new(
-                            rand::random::<f32>() * 50.0 - 25.0,
-                            rand::random::<f32>() * 50.0 - 25.0
-                        ),
-                        lifetime: Timer::from_seconds(1.0, TimerMode::Once),
-                    },
-                ));
-            }
+    let full = progress.any_full();
+
+    for mut emitter in emitter_query.iter_mut() {
+        if emitter.active != full {
+            emitter.active = full;
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn shoot_balloon(
     mut commands: Commands,
+    mut shot_events: EventWriter<ShotFired>,
     mouse_button: Res<ButtonInput<MouseButton>>,
-    cursor_pos: Res<CursorPosition>,
-    ghost_query: Query<&Transform, With<Ghost>>,
-    progress_bar_query: Query<&Style, With<ProgressBar>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    mut ghost_query: Query<(&Transform, &Player, Option<&mut RemoteInput>)>,
+    progress: Res<BalloonProgress>,
+    game_mode: Res<GameMode>,
+    versus_round: Res<VersusRound>,
+    settings: Res<Settings>,
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
 ) {
-    // Check if progress bar is at 100%
-    let can_shoot = progress_bar_query
-        .get_single()
-        .map(|style| {
-            if let Val::Percent(progress) = style.width {
-                progress >= 100.0
-            } else {
-                false
-            }
-        })
-        .unwrap_or(false);
+    let palette = settings.palette();
 
-    // Only allow shooting if progress bar is full
-    if can_shoot && (mouse_button.just_pressed(MouseButton::Left) || mouse_button.just_pressed(MouseButton::Right)) {
-        if let Ok(ghost_transform) = ghost_query.get_single() {
-            let direction = (cursor_pos.position - ghost_transform.translation.truncate()).normalize();
-            
+    for (ghost_transform, player, remote_input) in ghost_query.iter_mut() {
+        // Shots a remote player fires before unlocking are dropped, not queued
+        let remote_fire = remote_input.is_some_and(|mut input| std::mem::take(&mut input.pending_shots) > 0);
+
+        // Check if the player's progress bar is at 100%
+        let owner = progress_owner(player, *game_mode);
+        let can_shoot = progress.is_full(owner);
+
+        // In versus only the round's winner gets to pop the balloon
+        let round_won = *game_mode == GameMode::Coop || versus_round.winner == Some(player.id);
+
+        // Only allow shooting if progress bar is full
+        if !can_shoot || !round_won {
+            continue;
+        }
+
+        let bullet_color = match player.controls {
+            PlayerControls::Mouse if mouse_button.just_pressed(MouseButton::Left) => palette.primary_bullet(), // Red bullet
+            PlayerControls::Mouse if mouse_button.just_pressed(MouseButton::Right) => palette.secondary_bullet(), // Blue bullet
+            PlayerControls::Keyboard if keyboard.just_pressed(KeyCode::Space) => palette.primary_bullet(),
+            PlayerControls::Gamepad(gamepad) if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::South)) => palette.primary_bullet(),
+            PlayerControls::Gamepad(gamepad) if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::West)) => palette.secondary_bullet(),
+            PlayerControls::Remote if remote_fire => palette.primary_bullet(),
+            _ => continue,
+        };
+
+        // Split shot fans two extra bullets out either side of the aim
+        let spread: &[f32] = if upgrades.split_shot() { &[-15.0, 0.0, 15.0] } else { &[0.0] };
+        for angle in spread {
             commands.spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: if mouse_button.just_pressed(MouseButton::Left) {
-                            Color::srgb(1.0, 0.5, 0.5) // Red bullet
-                        } else {
-                            Color::srgb(0.5, 0.5, 1.0) // Blue bullet
-                        },
+                        color: bullet_color,
                         custom_size: Some(Vec2::new(10.0, 10.0)),
                         ..default()
                     },
@@ -1044,10 +1765,56 @@ fn shoot_balloon(
                     ..default()
                 },
                 Bullet {
-                    speed: 500.0,
-                    direction,
+                    speed: tuning.bullet_speed,
+                    direction: Vec2::from_angle(angle.to_radians()).rotate(player.aim),
                 },
+                LightSource::new(50.0, bullet_color, 0.8),
             ));
         }
+        shot_events.send(ShotFired {
+            player: player.id,
+            position: ghost_transform.translation,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursed_candy_at_zero_unbanks_to_zero() {
+        let mut inventory = PlayerInventory::default();
+
+        let banked = inventory.bank(CandyKind::CursedCandy);
+        assert_eq!(banked, 0);
+        assert_eq!(inventory.candies, 0);
+
+        inventory.unbank(CandyKind::CursedCandy, banked);
+        assert_eq!(inventory.candies, 0);
+        assert_eq!(inventory.collected[&CandyKind::CursedCandy], 0);
+    }
+
+    #[test]
+    fn cursed_candy_only_takes_back_what_it_cost() {
+        let mut inventory = PlayerInventory { candies: 1, ..default() };
+
+        let banked = inventory.bank(CandyKind::CursedCandy);
+        assert_eq!(banked, -1);
+        assert_eq!(inventory.candies, 0);
+
+        inventory.unbank(CandyKind::CursedCandy, banked);
+        assert_eq!(inventory.candies, 1);
+    }
+
+    #[test]
+    fn bank_and_unbank_round_trip() {
+        let mut inventory = PlayerInventory::default();
+
+        let banked = inventory.bank(CandyKind::FullSizeBar);
+        assert_eq!(inventory.candies, 4);
+
+        inventory.unbank(CandyKind::FullSizeBar, banked);
+        assert_eq!(inventory.candies, 0);
     }
 }
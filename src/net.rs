@@ -0,0 +1,817 @@
+// LAN multiplayer over UDP.
+//
+// `--server [addr]` runs the authoritative world: houses, balloon, bullets and
+// timers all live there, and every connected client gets a ghost of its own.
+// `--connect [addr]` runs a client that only sends its ghost's input and draws
+// whatever the server's snapshots say. Gameplay events the server's systems send
+// ride along with the snapshots and are sent again on the client, so sounds,
+// particles, stats and achievements react there too.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+
+use crate::{
+    candy::CandyKind,
+    events::{BalloonPopped, CandyCollected, CandyDeposited, HouseLightChanged, HousePranked, SackFull, ShotFired},
+    lighting::LightSource, settings::Settings, shop::Upgrades, tuning::GameTuning, pop_balloon, spawn_floating_text, spawn_player, BalloonProgress, BalloonPumpkin,
+    Bullet, CandySack, CoopSettings, CursorPosition, FloatingAnimation, GameMode, Ghost, GhostState, House,
+    HouseState, Player, PlayerControls, PlayerInventory, ProgressBarRow, ScoreText, VersusRound,
+    MAX_PLAYERS,
+};
+
+type SnapshotGhostQuery<'w, 's> = Query<'w, 's, (&'static Player, &'static Ghost, &'static Transform, &'static FloatingAnimation, &'static CandySack, Option<&'static PlayerInventory>)>;
+type ReplicaGhostQuery<'w, 's> = Query<'w, 's, (Entity, &'static Player, &'static mut Ghost, &'static mut Transform, &'static mut FloatingAnimation, &'static mut CandySack)>;
+type PlayerHudQuery<'w, 's> = Query<'w, 's, (Entity, Option<&'static ScoreText>, Option<&'static ProgressBarRow>), Or<(With<ScoreText>, With<ProgressBarRow>)>>;
+type ReplicaBulletQuery<'w, 's> = Query<'w, 's, (Entity, &'static mut Transform), (With<ReplicatedBullet>, Without<BalloonPumpkin>)>;
+
+const DEFAULT_SERVER_ADDR: &str = "0.0.0.0:7777";
+const DEFAULT_CONNECT_ADDR: &str = "127.0.0.1:7777";
+const SNAPSHOT_INTERVAL: f32 = 0.05;  // 20 snapshots a second
+const CLIENT_TIMEOUT: f32 = 5.0;      // Seconds of silence before a client is dropped
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ServerState>()
+            .init_resource::<ClientState>()
+            .add_systems(
+                Update,
+                (
+                    server_receive,
+                    server_drop_silent_clients,
+                    server_record_events,
+                    server_send_snapshots,
+                )
+                    .chain()
+                    .run_if(is_server),
+            )
+            .add_systems(
+                Update,
+                (
+                    client_send_input,
+                    client_receive,
+                    apply_snapshot_events,
+                    apply_house_snapshot,
+                    apply_ghost_snapshot,
+                    apply_world_snapshot,
+                )
+                    .chain()
+                    .run_if(is_client),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub enum NetRole {
+    Offline,
+    Server { socket: UdpSocket },
+    Client { socket: UdpSocket, server: SocketAddr },
+}
+
+impl NetRole {
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        // The address is optional, so only take the next argument if it isn't another flag
+        let flag_value = |flag: &str| {
+            args.iter().position(|arg| arg == flag).map(|index| {
+                args.get(index + 1)
+                    .filter(|value| !value.starts_with("--"))
+                    .cloned()
+            })
+        };
+
+        if let Some(addr) = flag_value("--server") {
+            let addr = addr.unwrap_or_else(|| DEFAULT_SERVER_ADDR.to_string());
+            let socket = UdpSocket::bind(&addr).expect("Failed to bind server socket");
+            socket.set_nonblocking(true).expect("Failed to make server socket non-blocking");
+            info!("Server listening on {}", addr);
+            NetRole::Server { socket }
+        } else if let Some(addr) = flag_value("--connect") {
+            let server = addr
+                .as_deref()
+                .unwrap_or(DEFAULT_CONNECT_ADDR)
+                .parse()
+                .expect("Invalid server address");
+            let socket = UdpSocket::bind("0.0.0.0:0").expect("Failed to bind client socket");
+            socket.set_nonblocking(true).expect("Failed to make client socket non-blocking");
+            info!("Connecting to {}", server);
+            NetRole::Client { socket, server }
+        } else {
+            NetRole::Offline
+        }
+    }
+
+    pub fn title_suffix(&self) -> &'static str {
+        match self {
+            NetRole::Offline => "",
+            NetRole::Server { .. } => " (server)",
+            NetRole::Client { .. } => " (client)",
+        }
+    }
+}
+
+// Run conditions
+
+// True everywhere the game simulates itself, i.e. offline or on the server
+pub fn is_authority(role: Res<NetRole>) -> bool {
+    !matches!(*role, NetRole::Client { .. })
+}
+
+pub fn is_offline(role: Res<NetRole>) -> bool {
+    matches!(*role, NetRole::Offline)
+}
+
+fn is_server(role: Res<NetRole>) -> bool {
+    matches!(*role, NetRole::Server { .. })
+}
+
+fn is_client(role: Res<NetRole>) -> bool {
+    matches!(*role, NetRole::Client { .. })
+}
+
+// Stable id shared by server and clients for entities both sides spawn
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct NetId(pub u32);
+
+// Latest input received for a ghost steered by a remote player
+#[derive(Component, Default)]
+pub struct RemoteInput {
+    pub pending_shots: u32,
+    pub pranking: bool,  // Holding the prank button, see `pranks`
+}
+
+// Marks the client-side stand-ins for the server's bullets
+#[derive(Component)]
+struct ReplicatedBullet;
+
+#[derive(Serialize, Deserialize)]
+enum ClientMessage {
+    Hello,
+    Input {
+        target: [f32; 2],
+        aim: [f32; 2],
+        shots: u32,  // Running total, so a lost packet never loses a shot
+        pranking: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+enum ServerMessage {
+    Welcome { player_id: usize, versus: bool },
+    Full,
+    Snapshot(WorldSnapshot),
+}
+
+#[derive(Serialize, Deserialize)]
+struct WorldSnapshot {
+    houses: Vec<HouseSnapshot>,
+    ghosts: Vec<GhostSnapshot>,
+    inventory: PlayerInventory,
+    progress: BalloonProgress,
+    balloon: bool,
+    bullets: Vec<[f32; 2]>,
+    versus_winner: Option<usize>,
+    events: Vec<NetEvent>,  // Since the last snapshot
+}
+
+// The gameplay events only the authority sends. Houses go by `NetId`, light
+// changes and the balloon pop are worked out from the snapshots themselves
+#[derive(Serialize, Deserialize)]
+enum NetEvent {
+    CandyCollected { player: usize, house: Option<u32>, kind: CandyKind, position: [f32; 3] },
+    CandyDeposited { player: usize, amount: u32, slots: u32, capacity: u32, position: [f32; 3] },
+    ShotFired { player: usize, position: [f32; 3] },
+    SackFull { player: usize },
+    HousePranked { player: usize, house: u32, position: [f32; 3], woke_up: bool },
+}
+
+#[derive(SystemParam)]
+struct GameplayEventReaders<'w, 's> {
+    collected: EventReader<'w, 's, CandyCollected>,
+    deposited: EventReader<'w, 's, CandyDeposited>,
+    shots: EventReader<'w, 's, ShotFired>,
+    sack_full: EventReader<'w, 's, SackFull>,
+    pranked: EventReader<'w, 's, HousePranked>,
+}
+
+#[derive(SystemParam)]
+struct GameplayEventWriters<'w> {
+    collected: EventWriter<'w, CandyCollected>,
+    deposited: EventWriter<'w, CandyDeposited>,
+    shots: EventWriter<'w, ShotFired>,
+    sack_full: EventWriter<'w, SackFull>,
+    pranked: EventWriter<'w, HousePranked>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HouseSnapshot {
+    id: u32,
+    light_status: bool,
+    interaction: f32,  // Furthest visitor's fraction, see `House::visit_fraction`
+    dimming: bool,  // Lights about to go out
+    prank: f32,  // `prank_timer` fraction
+}
+
+#[derive(Serialize, Deserialize)]
+struct GhostSnapshot {
+    player_id: usize,
+    // Transform, with y taken before the floating animation is applied
+    position: [f32; 2],
+    rotation: f32,
+    scale: f32,
+    faded: bool,
+    sack: CandySack,
+    inventory: Option<PlayerInventory>,
+}
+
+struct RemoteClient {
+    player_id: usize,
+    ghost: Entity,
+    shots: u32,
+    last_heard: f32,
+}
+
+#[derive(Resource)]
+struct ServerState {
+    clients: HashMap<SocketAddr, RemoteClient>,
+    snapshot_timer: Timer,
+    events: Vec<NetEvent>,  // Waiting for the next snapshot
+}
+
+impl Default for ServerState {
+    fn default() -> Self {
+        Self {
+            clients: HashMap::new(),
+            snapshot_timer: Timer::from_seconds(SNAPSHOT_INTERVAL, TimerMode::Repeating),
+            events: Vec::new(),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ClientState {
+    player_id: Option<usize>,
+    shots: u32,
+    hello_timer: Timer,
+    pending: Option<WorldSnapshot>,
+}
+
+impl Default for ClientState {
+    fn default() -> Self {
+        Self {
+            player_id: None,
+            shots: 0,
+            hello_timer: Timer::from_seconds(0.5, TimerMode::Repeating),
+            pending: None,
+        }
+    }
+}
+
+fn send<T: Serialize>(socket: &UdpSocket, addr: SocketAddr, message: &T) {
+    if let Ok(data) = serde_json::to_vec(message) {
+        // UDP is fire and forget; a dropped packet is covered by the next one
+        let _ = socket.send_to(&data, addr);
+    }
+}
+
+fn receive<T: for<'de> Deserialize<'de>>(socket: &UdpSocket) -> Vec<(T, SocketAddr)> {
+    let mut buffer = [0u8; 65536];
+    let mut messages = Vec::new();
+    while let Ok((len, addr)) = socket.recv_from(&mut buffer) {
+        if let Ok(message) = serde_json::from_slice(&buffer[..len]) {
+            messages.push((message, addr));
+        }
+    }
+    messages
+}
+
+// Server
+
+#[allow(clippy::too_many_arguments)]
+fn server_receive(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
+    net_role: Res<NetRole>,
+    mut server: ResMut<ServerState>,
+    time: Res<Time>,
+    coop_settings: Res<CoopSettings>,
+    game_mode: Res<GameMode>,
+    mut ghost_query: Query<(&mut Player, &mut RemoteInput)>,
+) {
+    let NetRole::Server { socket } = &*net_role else {
+        return;
+    };
+
+    for (message, addr) in receive::<ClientMessage>(socket) {
+        let now = time.elapsed_seconds();
+
+        match message {
+            ClientMessage::Hello => {
+                // Welcome may have been lost, so repeat it
+                if let Some(client) = server.clients.get_mut(&addr) {
+                    client.last_heard = now;
+                    send(socket, addr, &ServerMessage::Welcome {
+                        player_id: client.player_id,
+                        versus: *game_mode == GameMode::Versus,
+                    });
+                    continue;
+                }
+
+                let Some(player_id) = (0..MAX_PLAYERS)
+                    .find(|id| server.clients.values().all(|client| client.player_id != *id))
+                else {
+                    send(socket, addr, &ServerMessage::Full);
+                    continue;
+                };
+
+                let ghost = spawn_player(
+                    &mut commands,
+                    &asset_server,
+                    &tuning,
+                    &upgrades,
+                    player_id,
+                    PlayerControls::Remote,
+                    &coop_settings,
+                    *game_mode,
+                );
+                commands.entity(ghost).insert(RemoteInput::default());
+                server.clients.insert(addr, RemoteClient {
+                    player_id,
+                    ghost,
+                    shots: 0,
+                    last_heard: now,
+                });
+
+                info!("Player {} joined from {}", player_id + 1, addr);
+                send(socket, addr, &ServerMessage::Welcome {
+                    player_id,
+                    versus: *game_mode == GameMode::Versus,
+                });
+            }
+            ClientMessage::Input { target, aim, shots, pranking } => {
+                let Some(client) = server.clients.get_mut(&addr) else {
+                    continue;
+                };
+                client.last_heard = now;
+
+                if let Ok((mut player, mut remote_input)) = ghost_query.get_mut(client.ghost) {
+                    player.target = Vec2::from(target);
+                    if let Some(aim) = Vec2::from(aim).try_normalize() {
+                        player.aim = aim;
+                    }
+                    remote_input.pending_shots += shots.saturating_sub(client.shots);
+                    remote_input.pranking = pranking;
+                }
+                client.shots = client.shots.max(shots);
+            }
+        }
+    }
+}
+
+fn server_drop_silent_clients(
+    mut commands: Commands,
+    mut server: ResMut<ServerState>,
+    time: Res<Time>,
+    game_mode: Res<GameMode>,
+    mut progress: ResMut<BalloonProgress>,
+    hud_query: PlayerHudQuery,
+) {
+    let now = time.elapsed_seconds();
+
+    server.clients.retain(|addr, client| {
+        if now - client.last_heard < CLIENT_TIMEOUT {
+            return true;
+        }
+
+        info!("Player {} at {} timed out", client.player_id + 1, addr);
+        commands.entity(client.ghost).despawn_recursive();
+        despawn_player_hud(&mut commands, &hud_query, client.player_id);
+        // Co-op deposits went into the shared bar, which stays
+        if *game_mode == GameMode::Versus {
+            progress.set(client.player_id, 0.0);
+        }
+        false
+    });
+}
+
+// A departed player's score and progress bar
+fn despawn_player_hud(commands: &mut Commands, hud_query: &PlayerHudQuery, player: usize) {
+    for (entity, score_text, bar_row) in hud_query.iter() {
+        let owner = score_text.map(|text| text.player).or(bar_row.map(|row| row.player));
+        if owner == Some(player) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn server_record_events(
+    mut server: ResMut<ServerState>,
+    mut readers: GameplayEventReaders,
+    house_query: Query<&NetId>,
+) {
+    let house_id = |house: Entity| house_query.get(house).ok().map(|net_id| net_id.0);
+
+    let mut events = Vec::new();
+    for event in readers.collected.read() {
+        events.push(NetEvent::CandyCollected {
+            player: event.player,
+            house: event.house.and_then(house_id),
+            kind: event.kind,
+            position: event.position.to_array(),
+        });
+    }
+    for event in readers.deposited.read() {
+        events.push(NetEvent::CandyDeposited {
+            player: event.player,
+            amount: event.amount,
+            slots: event.slots,
+            capacity: event.capacity,
+            position: event.position.to_array(),
+        });
+    }
+    for event in readers.shots.read() {
+        events.push(NetEvent::ShotFired {
+            player: event.player,
+            position: event.position.to_array(),
+        });
+    }
+    for event in readers.sack_full.read() {
+        events.push(NetEvent::SackFull { player: event.player });
+    }
+    for event in readers.pranked.read() {
+        if let Some(house) = house_id(event.house) {
+            events.push(NetEvent::HousePranked {
+                player: event.player,
+                house,
+                position: event.position.to_array(),
+                woke_up: event.woke_up,
+            });
+        }
+    }
+
+    // Nobody to tell
+    if !server.clients.is_empty() {
+        server.events.append(&mut events);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn server_send_snapshots(
+    net_role: Res<NetRole>,
+    mut server: ResMut<ServerState>,
+    time: Res<Time>,
+    inventory: Res<PlayerInventory>,
+    versus_round: Res<VersusRound>,
+    house_query: Query<(&NetId, &House)>,
+    ghost_query: SnapshotGhostQuery,
+    progress: Res<BalloonProgress>,
+    balloon_query: Query<(), With<BalloonPumpkin>>,
+    bullet_query: Query<&Transform, With<Bullet>>,
+) {
+    let NetRole::Server { socket } = &*net_role else {
+        return;
+    };
+
+    server.snapshot_timer.tick(time.delta());
+    if !server.snapshot_timer.just_finished() || server.clients.is_empty() {
+        return;
+    }
+
+    let snapshot = WorldSnapshot {
+        houses: house_query
+            .iter()
+            .map(|(net_id, house)| HouseSnapshot {
+                id: net_id.0,
+                light_status: house.light_status,
+                interaction: house.visit_fraction(),
+                dimming: house.lights_out,
+                prank: house.prank_timer.fraction(),
+            })
+            .collect(),
+        ghosts: ghost_query
+            .iter()
+            .map(|(player, ghost, transform, anim, candy_sack, own_inventory)| GhostSnapshot {
+                player_id: player.id,
+                position: [transform.translation.x, anim.original_y],
+                rotation: transform.rotation.to_euler(EulerRot::XYZ).2,
+                scale: transform.scale.x,
+                faded: matches!(ghost.state, GhostState::Faded),
+                sack: candy_sack.clone(),
+                inventory: own_inventory.cloned(),
+            })
+            .collect(),
+        inventory: inventory.clone(),
+        progress: progress.clone(),
+        balloon: !balloon_query.is_empty(),
+        bullets: bullet_query
+            .iter()
+            .map(|transform| transform.translation.truncate().to_array())
+            .collect(),
+        versus_winner: versus_round.winner,
+        events: std::mem::take(&mut server.events),
+    };
+
+    let message = ServerMessage::Snapshot(snapshot);
+    for addr in server.clients.keys() {
+        send(socket, *addr, &message);
+    }
+}
+
+// Client
+
+fn client_send_input(
+    net_role: Res<NetRole>,
+    mut client: ResMut<ClientState>,
+    time: Res<Time>,
+    cursor_position: Res<CursorPosition>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    ghost_query: Query<(&Player, &Transform)>,
+) {
+    let NetRole::Client { socket, server } = &*net_role else {
+        return;
+    };
+
+    let Some(player_id) = client.player_id else {
+        // Keep knocking until the server lets us in
+        client.hello_timer.tick(time.delta());
+        if client.hello_timer.just_finished() {
+            send(socket, *server, &ClientMessage::Hello);
+        }
+        return;
+    };
+
+    if mouse_button.just_pressed(MouseButton::Left) || mouse_button.just_pressed(MouseButton::Right) {
+        client.shots += 1;
+    }
+
+    let aim = ghost_query
+        .iter()
+        .find(|(player, _)| player.id == player_id)
+        .and_then(|(_, transform)| (cursor_position.position - transform.translation.truncate()).try_normalize())
+        .unwrap_or(Vec2::Y);
+
+    send(socket, *server, &ClientMessage::Input {
+        target: cursor_position.position.to_array(),
+        aim: aim.to_array(),
+        shots: client.shots,
+        pranking: mouse_button.pressed(MouseButton::Middle),
+    });
+}
+
+fn client_receive(
+    net_role: Res<NetRole>,
+    mut client: ResMut<ClientState>,
+    mut game_mode: ResMut<GameMode>,
+) {
+    let NetRole::Client { socket, server } = &*net_role else {
+        return;
+    };
+
+    for (message, addr) in receive::<ServerMessage>(socket) {
+        if addr != *server {
+            continue;
+        }
+
+        match message {
+            ServerMessage::Welcome { player_id, versus } => {
+                if client.player_id.is_none() {
+                    info!("Joined as player {}", player_id + 1);
+                }
+                client.player_id = Some(player_id);
+                *game_mode = if versus { GameMode::Versus } else { GameMode::Coop };
+            }
+            ServerMessage::Full => {
+                warn!("Server is full, still trying...");
+            }
+            // Only the newest snapshot matters, apart from the events in older ones
+            ServerMessage::Snapshot(mut snapshot) => {
+                if let Some(mut older) = client.pending.take() {
+                    older.events.append(&mut snapshot.events);
+                    snapshot.events = older.events;
+                }
+                client.pending = Some(snapshot);
+            }
+        }
+    }
+}
+
+fn apply_snapshot_events(
+    mut client: ResMut<ClientState>,
+    mut writers: GameplayEventWriters,
+    house_query: Query<(Entity, &NetId)>,
+) {
+    let Some(snapshot) = client.pending.as_mut() else {
+        return;
+    };
+    let house_entity = |id: u32| house_query.iter().find(|(_, net_id)| net_id.0 == id).map(|(entity, _)| entity);
+
+    for event in std::mem::take(&mut snapshot.events) {
+        match event {
+            NetEvent::CandyCollected { player, house, kind, position } => {
+                writers.collected.send(CandyCollected {
+                    player,
+                    house: house.and_then(house_entity),
+                    kind,
+                    position: Vec3::from(position),
+                });
+            }
+            NetEvent::CandyDeposited { player, amount, slots, capacity, position } => {
+                writers.deposited.send(CandyDeposited {
+                    player,
+                    amount,
+                    slots,
+                    capacity,
+                    position: Vec3::from(position),
+                });
+            }
+            NetEvent::ShotFired { player, position } => {
+                writers.shots.send(ShotFired {
+                    player,
+                    position: Vec3::from(position),
+                });
+            }
+            NetEvent::SackFull { player } => {
+                writers.sack_full.send(SackFull { player });
+            }
+            NetEvent::HousePranked { player, house, position, woke_up } => {
+                if let Some(house) = house_entity(house) {
+                    writers.pranked.send(HousePranked {
+                        player,
+                        house,
+                        position: Vec3::from(position),
+                        woke_up,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn apply_house_snapshot(
+    client: Res<ClientState>,
+    mut house_query: Query<(Entity, &NetId, &mut House)>,
+    mut light_events: EventWriter<HouseLightChanged>,
+) {
+    let Some(snapshot) = &client.pending else {
+        return;
+    };
+
+    for (entity, net_id, mut house) in house_query.iter_mut() {
+        let Some(house_snapshot) = snapshot.houses.iter().find(|house| house.id == net_id.0) else {
+            continue;
+        };
+
+        if house.light_status != house_snapshot.light_status {
+            light_events.send(HouseLightChanged {
+                house: entity,
+                lit: house_snapshot.light_status,
+            });
+        }
+        house.light_status = house_snapshot.light_status;
+        house.state = if house.light_status { HouseState::Lit } else { HouseState::Dark };
+        house.lights_out = house_snapshot.dimming;
+        let elapsed = house.interaction_timer.duration().mul_f32(house_snapshot.interaction);
+        house.interaction_timer.set_elapsed(elapsed);
+        let elapsed = house.prank_timer.duration().mul_f32(house_snapshot.prank);
+        house.prank_timer.set_elapsed(elapsed);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_ghost_snapshot(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    upgrades: Res<Upgrades>,
+    client: Res<ClientState>,
+    coop_settings: Res<CoopSettings>,
+    game_mode: Res<GameMode>,
+    mut ghost_query: ReplicaGhostQuery,
+    hud_query: PlayerHudQuery,
+) {
+    let Some(snapshot) = &client.pending else {
+        return;
+    };
+
+    // Ghosts whose players have left the server
+    for (entity, player, ..) in ghost_query.iter() {
+        if snapshot.ghosts.iter().all(|ghost| ghost.player_id != player.id) {
+            commands.entity(entity).despawn_recursive();
+            despawn_player_hud(&mut commands, &hud_query, player.id);
+        }
+    }
+
+    for ghost_snapshot in &snapshot.ghosts {
+        let existing = ghost_query
+            .iter_mut()
+            .find(|(_, player, ..)| player.id == ghost_snapshot.player_id);
+
+        let Some((entity, _, mut ghost, mut transform, mut anim, mut candy_sack)) = existing else {
+            // First sighting; it gets positioned by the next snapshot
+            spawn_player(
+                &mut commands,
+                &asset_server,
+                &tuning,
+                &upgrades,
+                ghost_snapshot.player_id,
+                PlayerControls::Remote,
+                &coop_settings,
+                *game_mode,
+            );
+            continue;
+        };
+
+        transform.translation.x = ghost_snapshot.position[0];
+        anim.original_y = ghost_snapshot.position[1];
+        transform.rotation = Quat::from_rotation_z(ghost_snapshot.rotation);
+        transform.scale = Vec3::splat(ghost_snapshot.scale);
+
+        *candy_sack = ghost_snapshot.sack.clone();
+        if let Some(own_inventory) = &ghost_snapshot.inventory {
+            commands.entity(entity).insert(own_inventory.clone());
+        }
+
+        match (&ghost.state, ghost_snapshot.faded) {
+            (GhostState::Normal, true) => {
+                ghost.state = GhostState::Faded;
+            }
+            (GhostState::Faded, false) => {
+                ghost.state = GhostState::Normal;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_world_snapshot(
+    mut commands: Commands,
+    mut popped_events: EventWriter<BalloonPopped>,
+    mut client: ResMut<ClientState>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut versus_round: ResMut<VersusRound>,
+    settings: Res<Settings>,
+    mut progress: ResMut<BalloonProgress>,
+    balloon_query: Query<(Entity, &Transform), With<BalloonPumpkin>>,
+    mut bullet_query: ReplicaBulletQuery,
+) {
+    let Some(snapshot) = client.pending.take() else {
+        return;
+    };
+
+    *inventory = snapshot.inventory;
+
+    *progress = snapshot.progress;
+
+    if versus_round.winner.is_none() {
+        if let Some(winner) = snapshot.versus_winner {
+            spawn_floating_text(
+                &mut commands,
+                Vec3::new(0.0, 150.0, 10.0),
+                &format!("Player {} wins the balloon round!", winner + 1)
+            );
+        }
+    }
+    versus_round.winner = snapshot.versus_winner;
+
+    // The pop itself happened on the server; replay the effect locally
+    if !snapshot.balloon {
+        if let Ok((balloon_entity, balloon_transform)) = balloon_query.get_single() {
+            let balloon_pos = balloon_transform.translation;
+            popped_events.send(BalloonPopped { position: balloon_pos });
+            spawn_floating_text(&mut commands, balloon_pos, "JACKPOT! 💰✨");
+            pop_balloon(&mut commands, balloon_entity);
+        }
+    }
+
+    // Reuse the bullet sprites we already have, topping up or trimming as needed
+    let mut bullets = bullet_query.iter_mut();
+    for position in &snapshot.bullets {
+        if let Some((_, mut transform)) = bullets.next() {
+            transform.translation.x = position[0];
+            transform.translation.y = position[1];
+        } else {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: settings.palette().primary_bullet(),
+                        custom_size: Some(Vec2::new(10.0, 10.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(position[0], position[1], 1.0),
+                    ..default()
+                },
+                ReplicatedBullet,
+                LightSource::new(50.0, settings.palette().primary_bullet(), 0.8),
+            ));
+        }
+    }
+    for (entity, _) in bullets {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,476 @@
+// Sprite particles: one-shot bursts and continuous emitters.
+//
+// Effects live in `assets/effects/*.effect.ron` (or `.effect.json`). Each one is
+// a list of `BurstConfig` layers, and edits to the files are picked up while the
+// game runs. Dead particles are hidden and kept in a pool instead of being
+// despawned, and the next effect reuses them.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    ecs::system::SystemParam,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::TAU, fmt};
+
+use crate::{
+    events::{BalloonPopped, CandyCollected, CandyDeposited, HousePranked},
+    settings::Settings,
+    GameState,
+};
+
+// Dead particles kept around for reuse, anything beyond this is despawned
+const MAX_POOLED: usize = 256;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ParticleEffect>()
+            .register_asset_loader(ParticleEffectLoader)
+            .init_resource::<ParticlePool>()
+            .init_resource::<ParticleEffects>()
+            .add_systems(
+                Update,
+                (play_gameplay_effects, emit_particles, update_particles)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+// Keyframes over a particle's life, `t` runs from 0.0 (spawn) to 1.0 (death).
+// Values between keys are linearly interpolated. In effect files a curve is
+// just its list of keys, e.g. `[(0.0, 1.0), (1.0, 0.0)]`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Curve<T> {
+    pub keys: Vec<(f32, T)>,
+}
+
+pub trait Keyframe: Copy {
+    fn blend(self, other: Self, t: f32) -> Self;
+}
+
+impl Keyframe for f32 {
+    fn blend(self, other: Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Keyframe for [f32; 3] {
+    fn blend(self, other: Self, t: f32) -> Self {
+        [
+            self[0].lerp(other[0], t),
+            self[1].lerp(other[1], t),
+            self[2].lerp(other[2], t),
+        ]
+    }
+}
+
+impl<T: Keyframe> Curve<T> {
+    pub fn constant(value: T) -> Self {
+        Self { keys: vec![(0.0, value)] }
+    }
+
+    pub fn sample(&self, t: f32) -> Option<T> {
+        let (first, last) = (self.keys.first()?, self.keys.last()?);
+        if t <= first.0 {
+            return Some(first.1);
+        }
+
+        for pair in self.keys.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            if t <= end {
+                let span = (end - start).max(f32::EPSILON);
+                return Some(from.blend(to, (t - start) / span));
+            }
+        }
+        Some(last.1)
+    }
+}
+
+// One layer of an effect. Bursts spawn `count` particles in a ring, while
+// emitters spawn them one at a time at their own rate.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BurstConfig {
+    pub texture: String,
+    pub count: i32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+    pub spread: f32,  // Random angle jitter in radians, TAU for fully random
+    pub min_scale: f32,
+    pub max_scale: f32,
+    pub lifetime: f32,
+    pub z: f32,
+    pub gravity: [f32; 2],
+    pub drag: f32,  // Fraction of velocity lost per second
+    pub colour: Curve<[f32; 3]>,
+    pub alpha: Curve<f32>,
+    pub scale: Curve<f32>,  // Multiplier on the spawn scale
+}
+
+impl Default for BurstConfig {
+    fn default() -> Self {
+        Self {
+            texture: "sprites/money_shot.png".to_string(),
+            count: 1,
+            min_speed: 0.0,
+            max_speed: 0.0,
+            spread: 0.0,
+            min_scale: 1.0,
+            max_scale: 1.0,
+            lifetime: 1.0,
+            z: 2.0,
+            gravity: [0.0, 0.0],
+            drag: 0.0,
+            colour: Curve::constant([1.0, 1.0, 1.0]),
+            alpha: Curve::constant(1.0),
+            scale: Curve::constant(1.0),
+        }
+    }
+}
+
+impl BurstConfig {
+    fn colour_at(&self, t: f32) -> Color {
+        let [red, green, blue] = self.colour.sample(t).unwrap_or([1.0, 1.0, 1.0]);
+        let alpha = self.alpha.sample(t).unwrap_or(1.0);
+        Color::srgba(red, green, blue, alpha)
+    }
+}
+
+#[derive(Asset, TypePath, Clone, Serialize, Deserialize)]
+pub struct ParticleEffect {
+    pub layers: Vec<BurstConfig>,  // Fired together
+}
+
+#[derive(Debug)]
+pub enum ParticleEffectError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ParticleEffectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParticleEffectError::Io(error) => write!(f, "could not read effect: {}", error),
+            ParticleEffectError::Ron(error) => write!(f, "invalid effect RON: {}", error),
+            ParticleEffectError::Json(error) => write!(f, "invalid effect JSON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ParticleEffectError {}
+
+#[derive(Default)]
+struct ParticleEffectLoader;
+
+impl AssetLoader for ParticleEffectLoader {
+    type Asset = ParticleEffect;
+    type Settings = ();
+    type Error = ParticleEffectError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<ParticleEffect, ParticleEffectError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(ParticleEffectError::Io)?;
+
+        let is_json = load_context.path().extension().is_some_and(|extension| extension == "json");
+        if is_json {
+            serde_json::from_slice(&bytes).map_err(ParticleEffectError::Json)
+        } else {
+            ron::de::from_bytes(&bytes).map_err(ParticleEffectError::Ron)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["effect.ron", "effect.json"]
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Effect {
+    MoneyBurst,
+    SparkleShower,
+    CandyPickup,
+    Deposit,
+    Scare,
+}
+
+#[derive(Resource)]
+struct ParticleEffects {
+    money_burst: Handle<ParticleEffect>,
+    sparkle_shower: Handle<ParticleEffect>,
+    candy_pickup: Handle<ParticleEffect>,
+    deposit: Handle<ParticleEffect>,
+    scare: Handle<ParticleEffect>,
+}
+
+impl FromWorld for ParticleEffects {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            money_burst: asset_server.load("effects/money_burst.effect.ron"),
+            sparkle_shower: asset_server.load("effects/sparkle_shower.effect.ron"),
+            candy_pickup: asset_server.load("effects/candy_pickup.effect.ron"),
+            deposit: asset_server.load("effects/deposit.effect.ron"),
+            scare: asset_server.load("effects/scare.effect.ron"),
+        }
+    }
+}
+
+impl ParticleEffects {
+    fn handle(&self, effect: Effect) -> &Handle<ParticleEffect> {
+        match effect {
+            Effect::MoneyBurst => &self.money_burst,
+            Effect::SparkleShower => &self.sparkle_shower,
+            Effect::CandyPickup => &self.candy_pickup,
+            Effect::Deposit => &self.deposit,
+            Effect::Scare => &self.scare,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    velocity: Vec2,
+    lifetime: Timer,
+    base_scale: f32,
+    effect: Handle<ParticleEffect>,
+    layer: usize,
+    alive: bool,
+}
+
+impl Particle {
+    // Dead particles stay around hidden, waiting in the pool
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+}
+
+// Spawns particles continuously from a box around its transform
+#[derive(Component)]
+pub struct ParticleEmitter {
+    pub effect: Effect,  // Each particle comes from a random layer
+    pub rate: f32,  // Particles per second, before the density setting
+    pub area: Vec2,  // Half-size of the spawn box
+    pub active: bool,
+    accumulator: f32,
+}
+
+impl ParticleEmitter {
+    pub fn new(effect: Effect, rate: f32) -> Self {
+        Self {
+            effect,
+            rate,
+            area: Vec2::ZERO,
+            active: true,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn with_area(mut self, area: Vec2) -> Self {
+        self.area = area;
+        self
+    }
+}
+
+#[derive(Resource, Default)]
+struct ParticlePool {
+    free: Vec<Entity>,
+}
+
+// Fires particle effects, reusing pooled particles where it can
+#[derive(SystemParam)]
+pub struct Particles<'w> {
+    effects: Res<'w, ParticleEffects>,
+    assets: Res<'w, Assets<ParticleEffect>>,
+    pool: ResMut<'w, ParticlePool>,
+    settings: Res<'w, Settings>,
+    asset_server: Res<'w, AssetServer>,
+}
+
+impl Particles<'_> {
+    pub fn burst(&mut self, commands: &mut Commands, effect: Effect, position: Vec3) {
+        let handle = self.effects.handle(effect);
+        let Some(particle_effect) = self.assets.get(handle) else {
+            return;
+        };
+
+        for (layer, config) in particle_effect.layers.iter().enumerate() {
+            let count = self.settings.particle_count(config.count);
+            for i in 0..count {
+                let angle = (i as f32 / count as f32) * TAU;
+                let source = ParticleSource { config, effect: handle, layer };
+                spawn_particle(commands, &mut self.pool, &self.asset_server, source, position, angle);
+            }
+        }
+    }
+}
+
+// The layer a particle is spawned from, kept so it can be re-read after a reload
+struct ParticleSource<'a> {
+    config: &'a BurstConfig,
+    effect: &'a Handle<ParticleEffect>,
+    layer: usize,
+}
+
+fn spawn_particle(
+    commands: &mut Commands,
+    pool: &mut ParticlePool,
+    asset_server: &AssetServer,
+    source: ParticleSource,
+    position: Vec3,
+    angle: f32,
+) {
+    let config = source.config;
+    let angle = angle + (rand::random::<f32>() - 0.5) * config.spread;
+    let speed = config.min_speed + rand::random::<f32>() * (config.max_speed - config.min_speed);
+    let base_scale = config.min_scale + rand::random::<f32>() * (config.max_scale - config.min_scale);
+    let scale = base_scale * config.scale.sample(0.0).unwrap_or(1.0);
+
+    let bundle = (
+        SpriteBundle {
+            texture: asset_server.load(&config.texture),
+            transform: Transform::from_xyz(position.x, position.y, config.z)
+                .with_scale(Vec3::splat(scale))
+                .with_rotation(Quat::from_rotation_z(angle)),
+            sprite: Sprite {
+                color: config.colour_at(0.0),
+                ..default()
+            },
+            ..default()
+        },
+        Particle {
+            velocity: Vec2::from_angle(angle) * speed,
+            lifetime: Timer::from_seconds(config.lifetime, TimerMode::Once),
+            base_scale,
+            effect: source.effect.clone(),
+            layer: source.layer,
+            alive: true,
+        },
+    );
+
+    // Inserting over a pooled particle keeps its archetype, so no table moves
+    match pool.free.pop().and_then(|entity| commands.get_entity(entity)) {
+        Some(mut pooled) => {
+            pooled.insert(bundle);
+        }
+        None => {
+            commands.spawn(bundle);
+        }
+    }
+}
+
+fn play_gameplay_effects(
+    mut commands: Commands,
+    mut particles: Particles,
+    mut collected_events: EventReader<CandyCollected>,
+    mut deposited_events: EventReader<CandyDeposited>,
+    mut popped_events: EventReader<BalloonPopped>,
+    mut prank_events: EventReader<HousePranked>,
+) {
+    for event in collected_events.read() {
+        particles.burst(&mut commands, Effect::CandyPickup, event.position);
+    }
+    for event in deposited_events.read() {
+        particles.burst(&mut commands, Effect::Deposit, event.position);
+    }
+    for event in popped_events.read() {
+        particles.burst(&mut commands, Effect::MoneyBurst, event.position);
+    }
+    for event in prank_events.read() {
+        particles.burst(&mut commands, Effect::Scare, event.position);
+    }
+}
+
+fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Particles,
+    mut emitter_query: Query<(&GlobalTransform, &mut ParticleEmitter)>,
+) {
+    let density = particles.settings.particle_density;
+
+    for (transform, mut emitter) in emitter_query.iter_mut() {
+        if !emitter.active {
+            emitter.accumulator = 0.0;
+            continue;
+        }
+
+        emitter.accumulator += emitter.rate * density * time.delta_seconds();
+        while emitter.accumulator >= 1.0 {
+            emitter.accumulator -= 1.0;
+
+            let handle = particles.effects.handle(emitter.effect);
+            let Some(particle_effect) = particles.assets.get(handle) else {
+                break;
+            };
+            if particle_effect.layers.is_empty() {
+                break;
+            }
+
+            let layer = rand::random::<usize>() % particle_effect.layers.len();
+            let source = ParticleSource { config: &particle_effect.layers[layer], effect: handle, layer };
+            let offset = Vec2::new(
+                (rand::random::<f32>() * 2.0 - 1.0) * emitter.area.x,
+                (rand::random::<f32>() * 2.0 - 1.0) * emitter.area.y,
+            );
+            let position = transform.translation() + offset.extend(0.0);
+            let angle = rand::random::<f32>() * TAU;
+            spawn_particle(&mut commands, &mut particles.pool, &particles.asset_server, source, position, angle);
+        }
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    effects: Res<Assets<ParticleEffect>>,
+    mut pool: ResMut<ParticlePool>,
+    mut particles: Query<(Entity, &mut Transform, &mut Sprite, &mut Visibility, &mut Particle)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut transform, mut sprite, mut visibility, mut particle) in particles.iter_mut() {
+        if !particle.alive {
+            continue;
+        }
+
+        particle.lifetime.tick(time.delta());
+        if particle.lifetime.finished() {
+            particle.alive = false;
+            if pool.free.len() < MAX_POOLED {
+                *visibility = Visibility::Hidden;
+                pool.free.push(entity);
+            } else {
+                commands.entity(entity).despawn();
+            }
+            continue;
+        }
+
+        // A reload can drop the layer mid-flight, carry on in a straight line if so
+        let config = effects
+            .get(&particle.effect)
+            .and_then(|particle_effect| particle_effect.layers.get(particle.layer));
+        let Some(config) = config else {
+            transform.translation += (particle.velocity * delta).extend(0.0);
+            continue;
+        };
+
+        let gravity = Vec2::from(config.gravity);
+        particle.velocity += gravity * delta;
+        particle.velocity *= (1.0 - config.drag * delta).max(0.0);
+        transform.translation += (particle.velocity * delta).extend(0.0);
+
+        let t = particle.lifetime.fraction();
+        sprite.color = config.colour_at(t);
+        transform.scale = Vec3::splat(particle.base_scale * config.scale.sample(t).unwrap_or(1.0));
+    }
+}
@@ -0,0 +1,152 @@
+// Pranks on dark houses. A ghost next to a dark house can hold the prank
+// button (middle mouse, E, or the gamepad's East button) to rattle the windows.
+// Once the prank lands there's a scare, and either the residents switch the
+// lights back on to see what's going on, or the ghost gets away with it and
+// earns a spook point. Each dark spell can only be pranked once. Spook points
+// pay for the prank upgrades in the shop, see `shop`.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use std::time::Duration;
+
+use crate::{
+    events::{HouseLightChanged, HousePranked},
+    level::LightSchedule,
+    net::{self, RemoteInput},
+    set_house_lights,
+    shop::Upgrades,
+    spawn_floating_text,
+    tuning::GameTuning,
+    GameState, Ghost, House, Player, PlayerControls, PlayerInventory,
+};
+
+type PranksterQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Player, Option<&'static RemoteInput>, Option<&'static mut PlayerInventory>), With<Ghost>>;
+
+pub struct PranksPlugin;
+
+impl Plugin for PranksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                prank_dark_houses
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(net::is_authority),
+                rattle_houses,
+            )
+                .chain(),
+        );
+    }
+}
+
+// Whichever button each kind of player holds to prank
+#[derive(SystemParam)]
+struct PrankButtons<'w> {
+    mouse_button: Res<'w, ButtonInput<MouseButton>>,
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl PrankButtons<'_> {
+    fn held(&self, player: &Player, remote_input: Option<&RemoteInput>) -> bool {
+        match player.controls {
+            PlayerControls::Mouse => self.mouse_button.pressed(MouseButton::Middle),
+            PlayerControls::Keyboard => self.keyboard.pressed(KeyCode::KeyE),
+            PlayerControls::Gamepad(gamepad) => self
+                .gamepad_buttons
+                .pressed(GamepadButton::new(gamepad, GamepadButtonType::East)),
+            PlayerControls::Remote => remote_input.is_some_and(|input| input.pranking),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prank_dark_houses(
+    mut commands: Commands,
+    mut prank_events: EventWriter<HousePranked>,
+    mut light_events: EventWriter<HouseLightChanged>,
+    buttons: PrankButtons,
+    time: Res<Time>,
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
+    mut inventory: ResMut<PlayerInventory>,
+    mut ghost_query: PranksterQuery,
+    mut house_query: Query<(Entity, &Transform, &mut House, &mut LightSchedule)>,
+) {
+    let prank_range = upgrades.interaction_range(&tuning);
+
+    for (house_entity, house_transform, mut house, mut schedule) in house_query.iter_mut() {
+        if house.light_status || house.pranked {
+            continue;
+        }
+
+        // The closest ghost holding the prank button gets the credit
+        let prankster = ghost_query
+            .iter()
+            .filter(|(_, _, player, remote_input, _)| buttons.held(player, *remote_input))
+            .map(|(entity, ghost_transform, ..)| {
+                (entity, ghost_transform.translation.distance(house_transform.translation))
+            })
+            .filter(|(_, distance)| *distance < prank_range)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        let Some((prankster, _)) = prankster else {
+            house.prank_timer.reset();
+            continue;
+        };
+
+        let prank_duration = Duration::from_secs_f32(upgrades.prank_seconds(&tuning));
+        if house.prank_timer.duration() != prank_duration {
+            house.prank_timer.set_duration(prank_duration);
+        }
+        house.prank_timer.tick(time.delta());
+        if !house.prank_timer.just_finished() {
+            continue;
+        }
+
+        house.prank_timer.reset();
+        house.pranked = true;
+
+        let Ok((_, _, player, _, own_inventory)) = ghost_query.get_mut(prankster) else {
+            continue;
+        };
+
+        let woke_up = rand::random::<f32>() < upgrades.prank_wake_chance(&tuning);
+        if woke_up {
+            // They'll go back to bed once they've had a look around
+            schedule.wake(tuning.prank_wake_seconds);
+            set_house_lights(&mut house, true);
+            light_events.send(HouseLightChanged {
+                house: house_entity,
+                lit: true,
+            });
+            spawn_floating_text(&mut commands, house_transform.translation, "Lights on!");
+        } else {
+            match own_inventory {
+                Some(mut own_inventory) => own_inventory.spooks += 1,
+                None => inventory.spooks += 1,
+            }
+            spawn_floating_text(&mut commands, house_transform.translation, "Boo! +1 spook");
+        }
+
+        prank_events.send(HousePranked {
+            player: player.id,
+            house: house_entity,
+            position: house_transform.translation,
+            woke_up,
+        });
+    }
+}
+
+// A house being pranked shakes on its foundations, harder as the prank builds
+fn rattle_houses(
+    time: Res<Time>,
+    mut house_query: Query<(&House, &mut Transform)>,
+) {
+    for (house, mut transform) in house_query.iter_mut() {
+        let shake = if house.light_status { 0.0 } else { house.prank_timer.fraction() };
+        let rotation = Quat::from_rotation_z((time.elapsed_seconds() * 45.0).sin() * 0.05 * shake);
+        if transform.rotation != rotation {
+            transform.rotation = rotation;
+        }
+    }
+}
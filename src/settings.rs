@@ -0,0 +1,367 @@
+// Player settings, loaded from settings.json at startup and saved whenever they
+// change. F1 opens the in-game settings menu, which pauses the game.
+
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::GameState;
+
+const SETTINGS_PATH: &str = "settings.json";
+
+const RESOLUTIONS: [(f32, f32); 5] = [
+    (800.0, 600.0),
+    (1024.0, 768.0),
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+];
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            .add_systems(
+                Update,
+                (
+                    toggle_settings_menu,
+                    navigate_settings_menu.run_if(in_state(GameState::Paused)),
+                    update_settings_menu_text.run_if(in_state(GameState::Paused)),
+                    apply_window_settings,
+                    save_settings,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub resolution: (f32, f32),
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub screen_shake: bool,
+    pub particle_density: f32,  // Multiplier on how many particles effects spawn
+    pub colour_blind: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            resolution: RESOLUTIONS[0],
+            fullscreen: false,
+            vsync: true,
+            master_volume: 0.8,
+            music_volume: 0.5,
+            effects_volume: 1.0,
+            screen_shake: true,
+            particle_density: 1.0,
+            colour_blind: false,
+        }
+    }
+}
+
+impl Settings {
+    // Falls back to defaults if the file is missing or unreadable
+    pub fn load() -> Self {
+        fs::read_to_string(SETTINGS_PATH)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn window_resolution(&self) -> WindowResolution {
+        self.resolution.into()
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        if self.colour_blind {
+            Palette::ColourBlind
+        } else {
+            Palette::Standard
+        }
+    }
+
+    // Scales a particle count by the density setting, never below one
+    pub fn particle_count(&self, count: i32) -> i32 {
+        ((count as f32 * self.particle_density).round() as i32).max(1)
+    }
+}
+
+// Gameplay colours that need to stay readable for colour-blind players.
+// The colour-blind set avoids red/green pairs in favour of blue/orange.
+#[derive(Clone, Copy)]
+pub enum Palette {
+    Standard,
+    ColourBlind,
+}
+
+impl Palette {
+    // Tint for a house the ghost is trick-or-treating at
+    pub fn house_in_range(self) -> Color {
+        match self {
+            Palette::Standard => Color::srgb(0.8, 1.0, 0.8),
+            Palette::ColourBlind => Color::srgb(0.7, 0.85, 1.0),
+        }
+    }
+
+    pub fn progress_fill(self) -> Color {
+        match self {
+            Palette::Standard => Color::srgb(0.8, 0.4, 0.0),
+            Palette::ColourBlind => Color::srgb(0.0, 0.45, 0.7),
+        }
+    }
+
+    pub fn progress_full(self) -> Color {
+        match self {
+            Palette::Standard => Color::srgb(1.0, 0.5, 0.0),
+            Palette::ColourBlind => Color::srgb(0.9, 0.6, 0.0),
+        }
+    }
+
+    pub fn primary_bullet(self) -> Color {
+        match self {
+            Palette::Standard => Color::srgb(1.0, 0.5, 0.5),
+            Palette::ColourBlind => Color::srgb(1.0, 0.7, 0.2),
+        }
+    }
+
+    pub fn secondary_bullet(self) -> Color {
+        match self {
+            Palette::Standard => Color::srgb(0.5, 0.5, 1.0),
+            Palette::ColourBlind => Color::srgb(0.3, 0.6, 1.0),
+        }
+    }
+}
+
+// Menu rows, in display order
+#[derive(Clone, Copy, PartialEq)]
+enum SettingsRow {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    ScreenShake,
+    ParticleDensity,
+    ColourBlind,
+}
+
+const ROWS: [SettingsRow; 9] = [
+    SettingsRow::Resolution,
+    SettingsRow::Fullscreen,
+    SettingsRow::Vsync,
+    SettingsRow::MasterVolume,
+    SettingsRow::MusicVolume,
+    SettingsRow::EffectsVolume,
+    SettingsRow::ScreenShake,
+    SettingsRow::ParticleDensity,
+    SettingsRow::ColourBlind,
+];
+
+#[derive(Resource, Default)]
+struct SettingsMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct SettingsMenuRoot;
+
+#[derive(Component)]
+struct SettingsMenuText;
+
+fn on_off(value: bool) -> &'static str {
+    if value { "On" } else { "Off" }
+}
+
+fn percent(value: f32) -> String {
+    format!("{}%", (value * 100.0).round())
+}
+
+fn row_label(row: SettingsRow, settings: &Settings) -> String {
+    match row {
+        SettingsRow::Resolution => format!("Resolution: {}x{}", settings.resolution.0, settings.resolution.1),
+        SettingsRow::Fullscreen => format!("Fullscreen: {}", on_off(settings.fullscreen)),
+        SettingsRow::Vsync => format!("VSync: {}", on_off(settings.vsync)),
+        SettingsRow::MasterVolume => format!("Master Volume: {}", percent(settings.master_volume)),
+        SettingsRow::MusicVolume => format!("Music Volume: {}", percent(settings.music_volume)),
+        SettingsRow::EffectsVolume => format!("Effects Volume: {}", percent(settings.effects_volume)),
+        SettingsRow::ScreenShake => format!("Screen Shake: {}", on_off(settings.screen_shake)),
+        SettingsRow::ParticleDensity => format!("Particle Density: {}", percent(settings.particle_density)),
+        SettingsRow::ColourBlind => format!("Colour-blind Palette: {}", on_off(settings.colour_blind)),
+    }
+}
+
+// Steps a setting left (-1) or right (+1)
+fn change_row(row: SettingsRow, settings: &mut Settings, step: i32) {
+    let nudge = |value: f32, min: f32, max: f32| (value + 0.1 * step as f32).clamp(min, max);
+
+    match row {
+        SettingsRow::Resolution => {
+            let current = RESOLUTIONS
+                .iter()
+                .position(|resolution| *resolution == settings.resolution)
+                .unwrap_or(0) as i32;
+            let next = (current + step).rem_euclid(RESOLUTIONS.len() as i32);
+            settings.resolution = RESOLUTIONS[next as usize];
+        }
+        SettingsRow::Fullscreen => settings.fullscreen = !settings.fullscreen,
+        SettingsRow::Vsync => settings.vsync = !settings.vsync,
+        SettingsRow::MasterVolume => settings.master_volume = nudge(settings.master_volume, 0.0, 1.0),
+        SettingsRow::MusicVolume => settings.music_volume = nudge(settings.music_volume, 0.0, 1.0),
+        SettingsRow::EffectsVolume => settings.effects_volume = nudge(settings.effects_volume, 0.0, 1.0),
+        SettingsRow::ScreenShake => settings.screen_shake = !settings.screen_shake,
+        SettingsRow::ParticleDensity => {
+            settings.particle_density = (settings.particle_density + 0.25 * step as f32).clamp(0.25, 1.5);
+        }
+        SettingsRow::ColourBlind => settings.colour_blind = !settings.colour_blind,
+    }
+}
+
+fn toggle_settings_menu(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    menu_query: Query<Entity, With<SettingsMenuRoot>>,
+) {
+    let open = *state.get() == GameState::Paused;
+    let close_pressed = keyboard.just_pressed(KeyCode::F1) || keyboard.just_pressed(KeyCode::Escape);
+
+    if open && close_pressed {
+        for entity in menu_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        next_state.set(GameState::Playing);
+    } else if *state.get() == GameState::Playing && keyboard.just_pressed(KeyCode::F1) {
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                    z_index: ZIndex::Global(100),
+                    ..default()
+                },
+                SettingsMenuRoot,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    SettingsMenuText,
+                ));
+            });
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn navigate_settings_menu(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + ROWS.len() - 1) % ROWS.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % ROWS.len();
+    }
+
+    let step = if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        -1
+    } else if keyboard.just_pressed(KeyCode::ArrowRight) || keyboard.just_pressed(KeyCode::Enter) {
+        1
+    } else {
+        return;
+    };
+    change_row(ROWS[menu.selected], &mut settings, step);
+}
+
+fn update_settings_menu_text(
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    mut text_query: Query<&mut Text, With<SettingsMenuText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        let mut lines = vec!["SETTINGS".to_string(), String::new()];
+        for (index, row) in ROWS.iter().enumerate() {
+            let marker = if index == menu.selected { "> " } else { "  " };
+            lines.push(format!("{}{}", marker, row_label(*row, &settings)));
+        }
+        lines.push(String::new());
+        lines.push("Up/Down: select   Left/Right: change   F1/Esc: close".to_string());
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Ok(mut window) = window_query.get_single_mut() {
+        let (width, height) = settings.resolution;
+        if window.resolution.width() != width || window.resolution.height() != height {
+            window.resolution.set(width, height);
+        }
+        window.mode = settings.window_mode();
+        window.present_mode = settings.present_mode();
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    // Skip the frame it was loaded, nothing has changed yet
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    match serde_json::to_string_pretty(&*settings) {
+        Ok(data) => {
+            if let Err(error) = fs::write(SETTINGS_PATH, data) {
+                error!("Failed to save settings: {}", error);
+            }
+        }
+        Err(error) => error!("Failed to save settings: {}", error),
+    }
+}
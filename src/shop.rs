@@ -0,0 +1,387 @@
+// Upgrade shop, open between nights. Candy from the shared (or player one's)
+// inventory buys upgrades that apply to every ghost, and the prank upgrades are
+// paid for with spook points instead, see `pranks`. Upgrades are kept in the
+// save file along with the candy.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::{
+    events::{BalloonPopped, NightStarted},
+    net, tuning::GameTuning, CandySack, FadeEffect, GameState, Ghost, GhostState, House, PlayerInventory,
+};
+
+// Time to enjoy the jackpot before the shop opens
+const SHOP_DELAY_SECONDS: f32 = 2.5;
+
+pub struct ShopPlugin;
+
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Upgrades>()
+            .init_resource::<ShopMenu>()
+            .add_systems(
+                Update,
+                (
+                    open_shop.run_if(net::is_offline),
+                    navigate_shop.run_if(in_state(GameState::Shop)),
+                    update_shop_text.run_if(in_state(GameState::Shop)),
+                    apply_ghost_upgrades,
+                    apply_house_upgrades,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Upgrade {
+    BiggerSack,
+    FasterFlight,
+    QuickHands,
+    LongReach,
+    LongerFade,
+    SplitShot,
+    NimbleFingers,
+    SoftFootsteps,
+}
+
+const UPGRADES: [Upgrade; 8] = [
+    Upgrade::BiggerSack,
+    Upgrade::FasterFlight,
+    Upgrade::QuickHands,
+    Upgrade::LongReach,
+    Upgrade::LongerFade,
+    Upgrade::SplitShot,
+    Upgrade::NimbleFingers,
+    Upgrade::SoftFootsteps,
+];
+
+impl Upgrade {
+    fn name(self) -> &'static str {
+        match self {
+            Upgrade::BiggerSack => "Bigger Sack",
+            Upgrade::FasterFlight => "Faster Flight",
+            Upgrade::QuickHands => "Quick Hands",
+            Upgrade::LongReach => "Long Reach",
+            Upgrade::LongerFade => "Longer Fade",
+            Upgrade::SplitShot => "Split Shot",
+            Upgrade::NimbleFingers => "Nimble Fingers",
+            Upgrade::SoftFootsteps => "Soft Footsteps",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Upgrade::BiggerSack => "+2 sack capacity",
+            Upgrade::FasterFlight => "+15% flight speed",
+            Upgrade::QuickHands => "Trick-or-treat 15% faster",
+            Upgrade::LongReach => "+20 house interaction range",
+            Upgrade::LongerFade => "Stay Faded 1s longer",
+            Upgrade::SplitShot => "Fire three bullets in a spread",
+            Upgrade::NimbleFingers => "Pranks land 20% faster",
+            Upgrade::SoftFootsteps => "Pranks 30% less likely to wake the residents",
+        }
+    }
+
+    fn base_cost(self) -> u32 {
+        match self {
+            Upgrade::BiggerSack => 10,
+            Upgrade::FasterFlight => 8,
+            Upgrade::QuickHands => 12,
+            Upgrade::LongReach => 8,
+            Upgrade::LongerFade => 6,
+            Upgrade::SplitShot => 25,
+            Upgrade::NimbleFingers => 3,
+            Upgrade::SoftFootsteps => 4,
+        }
+    }
+
+    fn max_level(self) -> u32 {
+        match self {
+            Upgrade::SplitShot => 1,
+            Upgrade::SoftFootsteps => 2,
+            _ => 3,
+        }
+    }
+
+    // Prank upgrades cost spook points rather than candy
+    fn paid_in_spooks(self) -> bool {
+        matches!(self, Upgrade::NimbleFingers | Upgrade::SoftFootsteps)
+    }
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Upgrades {
+    levels: BTreeMap<Upgrade, u32>,
+}
+
+impl Upgrades {
+    fn level(&self, upgrade: Upgrade) -> u32 {
+        self.levels.get(&upgrade).copied().unwrap_or(0)
+    }
+
+    // Each level costs more than the last, None once maxed out
+    fn cost(&self, upgrade: Upgrade) -> Option<u32> {
+        let level = self.level(upgrade);
+        (level < upgrade.max_level()).then(|| upgrade.base_cost() * (level + 1))
+    }
+
+    // The rest build on the tuned values from before any upgrades
+    pub fn sack_capacity(&self, tuning: &GameTuning) -> u32 {
+        tuning.sack_capacity + 2 * self.level(Upgrade::BiggerSack)
+    }
+
+    pub fn ghost_speed(&self, tuning: &GameTuning) -> f32 {
+        tuning.ghost_speed * (1.0 + 0.15 * self.level(Upgrade::FasterFlight) as f32)
+    }
+
+    pub fn interaction_seconds(&self, tuning: &GameTuning) -> f32 {
+        tuning.interaction_seconds * 0.85_f32.powi(self.level(Upgrade::QuickHands) as i32)
+    }
+
+    pub fn interaction_range(&self, tuning: &GameTuning) -> f32 {
+        tuning.interaction_range + 20.0 * self.level(Upgrade::LongReach) as f32
+    }
+
+    pub fn faded_seconds(&self, tuning: &GameTuning) -> f32 {
+        tuning.fade_seconds + self.level(Upgrade::LongerFade) as f32
+    }
+
+    pub fn split_shot(&self) -> bool {
+        self.level(Upgrade::SplitShot) > 0
+    }
+
+    pub fn prank_seconds(&self, tuning: &GameTuning) -> f32 {
+        tuning.prank_seconds * 0.8_f32.powi(self.level(Upgrade::NimbleFingers) as i32)
+    }
+
+    pub fn prank_wake_chance(&self, tuning: &GameTuning) -> f32 {
+        let quieter = 0.7_f32.powi(self.level(Upgrade::SoftFootsteps) as i32);
+        (tuning.prank_wake_chance * tuning.aggression * quieter).min(1.0)
+    }
+}
+
+#[derive(Resource, Default)]
+struct ShopMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct ShopRoot;
+
+#[derive(Component)]
+struct ShopText;
+
+fn open_shop(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popped_events: EventReader<BalloonPopped>,
+    mut countdown: Local<Option<Timer>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if popped_events.read().count() > 0 {
+        *countdown = Some(Timer::from_seconds(SHOP_DELAY_SECONDS, TimerMode::Once));
+    }
+
+    let Some(timer) = countdown.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() {
+        return;
+    }
+    *countdown = None;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.8).into(),
+                z_index: ZIndex::Global(60),
+                ..default()
+            },
+            ShopRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                ShopText,
+            ));
+        });
+    next_state.set(GameState::Shop);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn navigate_shop(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<ShopMenu>,
+    mut upgrades: ResMut<Upgrades>,
+    mut inventory: ResMut<PlayerInventory>,
+    shop_query: Query<Entity, With<ShopRoot>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut night_events: EventWriter<NightStarted>,
+) {
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + UPGRADES.len() - 1) % UPGRADES.len();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % UPGRADES.len();
+    }
+
+    if keyboard.just_pressed(KeyCode::Enter) {
+        let upgrade = UPGRADES[menu.selected];
+        if let Some(cost) = upgrades.cost(upgrade) {
+            let wallet = if upgrade.paid_in_spooks() { &mut inventory.spooks } else { &mut inventory.candies };
+            if *wallet >= cost {
+                *wallet -= cost;
+                *upgrades.levels.entry(upgrade).or_insert(0) += 1;
+            }
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Space) {
+        for entity in shop_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        next_state.set(GameState::Playing);
+        night_events.send(NightStarted);
+    }
+}
+
+fn update_shop_text(
+    menu: Res<ShopMenu>,
+    upgrades: Res<Upgrades>,
+    inventory: Res<PlayerInventory>,
+    mut text_query: Query<&mut Text, With<ShopText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        let mut lines = vec![
+            "UPGRADE SHOP".to_string(),
+            format!("Candies: {}   Spooks: {}", inventory.candies, inventory.spooks),
+            String::new(),
+        ];
+        for (index, upgrade) in UPGRADES.iter().enumerate() {
+            let marker = if index == menu.selected { "> " } else { "  " };
+            let price = match upgrades.cost(*upgrade) {
+                Some(cost) if upgrade.paid_in_spooks() => format!("{} spooks", cost),
+                Some(cost) => format!("{} candies", cost),
+                None => "sold out".to_string(),
+            };
+            lines.push(format!(
+                "{}{} ({}/{}) - {}: {}",
+                marker,
+                upgrade.name(),
+                upgrades.level(*upgrade),
+                upgrade.max_level(),
+                price,
+                upgrade.description()
+            ));
+        }
+        lines.push(String::new());
+        lines.push("Up/Down: select   Enter: buy   Space: start the next night".to_string());
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+// Also reapplied when the tuning file is edited
+fn apply_ghost_upgrades(
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
+    mut ghost_query: Query<(&mut Ghost, &mut CandySack, &mut FadeEffect)>,
+) {
+    for (mut ghost, mut candy_sack, mut fade) in ghost_query.iter_mut() {
+        if !upgrades.is_changed() && !tuning.is_changed() && !ghost.is_added() {
+            continue;
+        }
+
+        ghost.speed = upgrades.ghost_speed(&tuning);
+        candy_sack.capacity = upgrades.sack_capacity(&tuning);
+        // Only the faded half of the fade cycle gets longer, see `fade_ghost`
+        let fade_seconds = match ghost.state {
+            GhostState::Faded => upgrades.faded_seconds(&tuning),
+            GhostState::Normal => tuning.fade_seconds,
+        };
+        fade.timer.set_duration(Duration::from_secs_f32(fade_seconds));
+    }
+}
+
+fn apply_house_upgrades(
+    upgrades: Res<Upgrades>,
+    tuning: Res<GameTuning>,
+    mut house_query: Query<&mut House>,
+) {
+    for mut house in house_query.iter_mut() {
+        if upgrades.is_changed() || tuning.is_changed() || house.is_added() {
+            house.interaction_timer.set_duration(Duration::from_secs_f32(upgrades.interaction_seconds(&tuning)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_cost_more_until_sold_out() {
+        let mut upgrades = Upgrades::default();
+        assert_eq!(upgrades.cost(Upgrade::BiggerSack), Some(10));
+
+        upgrades.levels.insert(Upgrade::BiggerSack, 2);
+        assert_eq!(upgrades.cost(Upgrade::BiggerSack), Some(30));
+
+        upgrades.levels.insert(Upgrade::BiggerSack, 3);
+        assert_eq!(upgrades.cost(Upgrade::BiggerSack), None);
+    }
+
+    #[test]
+    fn upgrades_build_on_the_tuning() {
+        let tuning = GameTuning::default();
+        let mut upgrades = Upgrades::default();
+        assert_eq!(upgrades.sack_capacity(&tuning), tuning.sack_capacity);
+        assert!(!upgrades.split_shot());
+
+        upgrades.levels.insert(Upgrade::BiggerSack, 1);
+        upgrades.levels.insert(Upgrade::SplitShot, 1);
+        assert_eq!(upgrades.sack_capacity(&tuning), tuning.sack_capacity + 2);
+        assert!(upgrades.split_shot());
+    }
+
+    #[test]
+    fn prank_upgrades_cost_spooks() {
+        assert!(Upgrade::NimbleFingers.paid_in_spooks());
+        assert!(Upgrade::SoftFootsteps.paid_in_spooks());
+        assert!(!Upgrade::BiggerSack.paid_in_spooks());
+    }
+
+    #[test]
+    fn soft_footsteps_keep_the_residents_asleep() {
+        let tuning = GameTuning {
+            prank_wake_chance: 0.8,
+            aggression: 2.0,
+            ..default()
+        };
+        let mut upgrades = Upgrades::default();
+        assert_eq!(upgrades.prank_wake_chance(&tuning), 1.0);
+
+        upgrades.levels.insert(Upgrade::SoftFootsteps, 2);
+        assert!(upgrades.prank_wake_chance(&tuning) < 0.8);
+    }
+}
@@ -0,0 +1,311 @@
+// Per-session statistics for balance tuning. The night report pops up when the
+// balloon is popped (Tab shows it at any time) and the numbers are exported to
+// stats/night-<unix time>.json.
+
+use bevy::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    events::{BalloonPopped, CandyCollected, CandyDeposited, HousePranked, NightStarted, ShotFired},
+    net::NetId,
+    GameState, Ghost, GhostState, Player, MAX_PLAYERS,
+};
+
+const STATS_DIR: &str = "stats";
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionStats>()
+            .add_systems(
+                Update,
+                (
+                    record_candy,
+                    record_deposits,
+                    record_shots,
+                    record_pranks,
+                    track_ghosts.run_if(in_state(GameState::Playing)),
+                    end_night,
+                    start_night,
+                    toggle_night_report,
+                    update_night_report,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Serialize, Default)]
+pub struct SessionStats {
+    pub night_seconds: f32,
+    pub candies_per_house: BTreeMap<u32, u32>,  // Keyed by house number
+    pub candies_picked_up: u32,  // Knocked out of a sack and grabbed again
+    pub deposits: u32,
+    pub candies_deposited: u32,
+    pub average_sack_fullness: f32,  // 0.0 to 1.0, measured on each deposit
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub pranks: u32,
+    pub residents_woken: u32,  // Pranks that got the lights switched back on
+    pub time_faded: [f32; MAX_PLAYERS],
+    pub distance_flown: [f32; MAX_PLAYERS],
+    #[serde(skip)]
+    night_over: bool,
+}
+
+impl SessionStats {
+    fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots_fired as f32
+        }
+    }
+
+    fn report_lines(&self) -> Vec<String> {
+        let house_candies: u32 = self.candies_per_house.values().sum();
+        let busiest_house = self
+            .candies_per_house
+            .iter()
+            .max_by_key(|(_, candies)| **candies)
+            .map(|(house, candies)| format!("house {} ({} candies)", house + 1, candies))
+            .unwrap_or_else(|| "none".to_string());
+
+        let mut lines = vec![
+            format!("Night length: {:.0}s", self.night_seconds),
+            format!("Candies from houses: {} (busiest: {})", house_candies, busiest_house),
+            format!("Candies picked up off the ground: {}", self.candies_picked_up),
+            format!("Deposits: {} ({} candies)", self.deposits, self.candies_deposited),
+            format!("Average sack fullness: {:.0}%", self.average_sack_fullness * 100.0),
+            format!(
+                "Shots: {} fired, {} hit ({:.0}%)",
+                self.shots_fired,
+                self.hits,
+                self.accuracy() * 100.0
+            ),
+            format!("Pranks: {} ({} woke the residents)", self.pranks, self.residents_woken),
+        ];
+        for player in 0..MAX_PLAYERS {
+            if self.distance_flown[player] > 0.0 {
+                lines.push(format!(
+                    "Player {}: flew {:.0}px, Faded for {:.0}s",
+                    player + 1,
+                    self.distance_flown[player],
+                    self.time_faded[player]
+                ));
+            }
+        }
+        lines
+    }
+
+    fn export(&self) {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let path = format!("{}/night-{}.json", STATS_DIR, seconds);
+
+        let result = fs::create_dir_all(STATS_DIR)
+            .and_then(|_| serde_json::to_string_pretty(self).map_err(std::io::Error::from))
+            .and_then(|data| fs::write(&path, data));
+        match result {
+            Ok(()) => info!("Night stats written to {}", path),
+            Err(error) => error!("Failed to write night stats: {}", error),
+        }
+    }
+}
+
+#[derive(Component)]
+struct NightReport;
+
+#[derive(Component)]
+struct NightReportText;
+
+fn record_candy(
+    mut stats: ResMut<SessionStats>,
+    mut collected_events: EventReader<CandyCollected>,
+    house_query: Query<&NetId>,
+) {
+    for event in collected_events.read() {
+        match event.house.and_then(|house| house_query.get(house).ok()) {
+            Some(house_id) => *stats.candies_per_house.entry(house_id.0).or_insert(0) += 1,
+            None => stats.candies_picked_up += 1,
+        }
+    }
+}
+
+fn record_deposits(
+    mut stats: ResMut<SessionStats>,
+    mut deposited_events: EventReader<CandyDeposited>,
+) {
+    for event in deposited_events.read() {
+        let fullness = event.slots as f32 / event.capacity.max(1) as f32;
+        stats.deposits += 1;
+        stats.candies_deposited += event.amount;
+        // Running average, so nothing needs keeping per deposit
+        stats.average_sack_fullness += (fullness - stats.average_sack_fullness) / stats.deposits as f32;
+    }
+}
+
+fn record_shots(
+    mut stats: ResMut<SessionStats>,
+    mut shot_events: EventReader<ShotFired>,
+) {
+    for _ in shot_events.read() {
+        stats.shots_fired += 1;
+    }
+}
+
+fn record_pranks(
+    mut stats: ResMut<SessionStats>,
+    mut prank_events: EventReader<HousePranked>,
+) {
+    for event in prank_events.read() {
+        stats.pranks += 1;
+        if event.woke_up {
+            stats.residents_woken += 1;
+        }
+    }
+}
+
+fn track_ghosts(
+    time: Res<Time>,
+    mut stats: ResMut<SessionStats>,
+    ghost_query: Query<(&Ghost, &Player)>,
+) {
+    if stats.night_over {
+        return;
+    }
+
+    let delta = time.delta_seconds();
+    stats.night_seconds += delta;
+
+    for (ghost, player) in ghost_query.iter() {
+        let Some(distance) = stats.distance_flown.get_mut(player.id) else {
+            continue;
+        };
+        *distance += player.velocity.length() * delta;
+
+        if matches!(ghost.state, GhostState::Faded) {
+            stats.time_faded[player.id] += delta;
+        }
+    }
+}
+
+fn end_night(
+    mut commands: Commands,
+    mut stats: ResMut<SessionStats>,
+    mut popped_events: EventReader<BalloonPopped>,
+    report_query: Query<(), With<NightReport>>,
+) {
+    // There's one balloon a night, anything after the first pop is stale
+    for _ in popped_events.read() {
+        if stats.night_over {
+            continue;
+        }
+
+        stats.hits += 1;
+        stats.night_over = true;
+        stats.export();
+        if report_query.is_empty() {
+            spawn_night_report(&mut commands);
+        }
+    }
+}
+
+// The shop has closed, last night's report goes with it
+fn start_night(
+    mut commands: Commands,
+    mut stats: ResMut<SessionStats>,
+    mut night_events: EventReader<NightStarted>,
+    report_query: Query<Entity, With<NightReport>>,
+) {
+    if night_events.read().count() == 0 {
+        return;
+    }
+
+    *stats = SessionStats::default();
+    for entity in report_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn toggle_night_report(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    report_query: Query<Entity, With<NightReport>>,
+) {
+    if !keyboard.just_pressed(KeyCode::Tab) {
+        return;
+    }
+
+    if report_query.is_empty() {
+        spawn_night_report(&mut commands);
+    } else {
+        for entity in report_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_night_report(commands: &mut Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(40),
+                ..default()
+            },
+            NightReport,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::srgba(0.05, 0.02, 0.1, 0.85).into(),
+                    border_radius: BorderRadius::all(Val::Px(8.0)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font_size: 22.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                        NightReportText,
+                    ));
+                });
+        });
+}
+
+fn update_night_report(
+    stats: Res<SessionStats>,
+    mut text_query: Query<&mut Text, With<NightReportText>>,
+) {
+    for mut text in text_query.iter_mut() {
+        let title = if stats.night_over { "NIGHT REPORT" } else { "NIGHT SO FAR" };
+        let mut lines = vec![title.to_string(), String::new()];
+        lines.extend(stats.report_lines());
+        lines.push(String::new());
+        lines.push("Tab: close".to_string());
+        text.sections[0].value = lines.join("\n");
+    }
+}
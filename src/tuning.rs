@@ -0,0 +1,164 @@
+// Balance numbers in one place. They're read from assets/game.tuning.ron and
+// edits to the file are picked up while the game runs, so designers can tweak
+// them without rebuilding. Until the file has loaded (or if it's broken) the
+// defaults below are used. The difficulty scales some of them, see
+// `difficulty`, and upgrades build on top of that, see `shop`.
+//
+// When the house lights go on and off isn't in here, that's per house type in
+// the level file, see `level`. `light_pace` only speeds those patterns up or
+// slows them down.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::difficulty::Difficulty;
+
+const TUNING_PATH: &str = "game.tuning.ron";
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameTuning>()
+            .register_asset_loader(GameTuningLoader)
+            .init_resource::<GameTuning>()
+            .init_resource::<TuningHandle>()
+            .add_systems(PreUpdate, apply_tuning);
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameTuning {
+    pub sack_capacity: u32,  // Slots, see `candy`
+    pub progress_per_full_sack: f32,  // Percent of the balloon bar, whatever candy fills the sack
+    pub interaction_seconds: f32,  // Trick-or-treating at a house
+    pub interaction_range: f32,
+    pub ghost_speed: f32,
+    pub fade_seconds: f32,  // Each half of the fade cycle
+    pub bullet_speed: f32,  // Pixels per second
+    pub trail_spawn_seconds: f32,
+    pub trail_lifetime_seconds: f32,
+    pub light_pace: f32,  // How fast houses run through their light patterns
+    pub balloon_float_speed: f32,  // How fast the balloon bobs up and down
+    pub full_sack_slowdown: f32,  // Fraction of a ghost's speed lost with a full sack
+    pub full_sack_float_damping: f32,  // Fraction of the float lost with a full sack
+    pub prank_seconds: f32,  // Holding the prank button at a dark house
+    pub prank_wake_chance: f32,  // Chance a prank gets the lights switched back on
+    pub prank_wake_seconds: f32,  // How long the woken residents keep them on
+    pub aggression: f32,  // Scales prank wake-ups and the candy spilled in versus bumps
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            sack_capacity: 10,
+            progress_per_full_sack: 25.0,
+            interaction_seconds: 3.0,
+            interaction_range: 100.0,
+            ghost_speed: 10.0,
+            fade_seconds: 3.0,
+            bullet_speed: 500.0,
+            trail_spawn_seconds: 0.05,
+            trail_lifetime_seconds: 0.8,
+            light_pace: 1.0,
+            balloon_float_speed: 1.5,
+            full_sack_slowdown: 0.35,
+            full_sack_float_damping: 0.6,
+            prank_seconds: 2.0,
+            prank_wake_chance: 0.25,
+            prank_wake_seconds: 6.0,
+            aggression: 1.0,
+        }
+    }
+}
+
+impl GameTuning {
+    // Keeps a hand-edited file or a difficulty from breaking the game
+    pub fn clamp(&mut self) {
+        self.sack_capacity = self.sack_capacity.max(1);
+        // A full sack never stops a ghost completely
+        self.full_sack_slowdown = self.full_sack_slowdown.min(0.9);
+        self.full_sack_float_damping = self.full_sack_float_damping.min(1.0);
+    }
+}
+
+#[derive(Debug)]
+pub enum GameTuningError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for GameTuningError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameTuningError::Io(error) => write!(f, "could not read tuning: {}", error),
+            GameTuningError::Ron(error) => write!(f, "invalid tuning RON: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for GameTuningError {}
+
+#[derive(Default)]
+struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    type Asset = GameTuning;
+    type Settings = ();
+    type Error = GameTuningError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<GameTuning, GameTuningError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(GameTuningError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(GameTuningError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+// Keeps the asset loaded so it can be watched
+#[derive(Resource)]
+struct TuningHandle(Handle<GameTuning>);
+
+impl FromWorld for TuningHandle {
+    fn from_world(world: &mut World) -> Self {
+        Self(world.resource::<AssetServer>().load(TUNING_PATH))
+    }
+}
+
+// Rebuilds the resource whenever the file (re)loads or a save brings its own
+// difficulty along
+fn apply_tuning(
+    mut asset_events: EventReader<AssetEvent<GameTuning>>,
+    handle: Res<TuningHandle>,
+    assets: Res<Assets<GameTuning>>,
+    difficulty: Res<Difficulty>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    let reloaded = asset_events
+        .read()
+        .filter(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0))
+        .count()
+        > 0;
+    if !reloaded && !difficulty.is_changed() {
+        return;
+    }
+
+    let mut adjusted = assets.get(&handle.0).cloned().unwrap_or_default();
+    difficulty.adjust(&mut adjusted);
+    adjusted.clamp();
+    *tuning = adjusted;
+    info!("Tuning applied for {:?}", *difficulty);
+}